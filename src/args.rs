@@ -0,0 +1,36 @@
+#![allow(unused)]
+
+use std::env;
+
+/// The argument following `name` on the command line, as in `--name value`
+pub fn value(name: &str) -> Option<String> {
+    value_in(env::args(), name)
+}
+
+/// Whether `name` appears anywhere on the command line
+pub fn flag(name: &str) -> bool {
+    env::args().any(|arg| arg == name)
+}
+
+fn value_in(args: impl Iterator<Item=String>, name: &str) -> Option<String> {
+    let mut args = args.skip_while(|arg| arg != name);
+    args.next()?;
+    args.next()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(line: &str) -> impl Iterator<Item=String> + '_ {
+        line.split_whitespace().map(String::from)
+    }
+
+    #[test]
+    fn finds_value_after_name() {
+        assert_eq!(Some("out.csv".to_string()), value_in(args("day --csv out.csv --x"), "--csv"));
+        assert_eq!(Some("--x".to_string()), value_in(args("day --csv --x"), "--csv"));
+        assert_eq!(None, value_in(args("day --csv"), "--csv"));
+        assert_eq!(None, value_in(args("day out.csv"), "--csv"));
+    }
+}
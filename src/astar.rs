@@ -173,6 +173,54 @@ mod test {
         fn point_order(a: &Point, b: &Point) -> Ordering { a.cmp_reading_order(*b) }
     }
 
+    struct ChebyshevHeuristic;
+
+    impl Heuristic for ChebyshevHeuristic {
+        type Item = Point;
+        type Score = usize;
+
+        fn score(from: &Point, to: &Point) -> usize { from.chebyshev_dist_to(*to) }
+        fn zero_score() -> usize { 0 }
+        fn infinity_score() -> usize { usize::MAX }
+    }
+
+    struct MooreWorld;
+
+    impl World for MooreWorld {
+        type Point = Point;
+        type Score = usize;
+        type Neighbors = MooreNeighbors;
+        type Heuristic = ChebyshevHeuristic;
+
+        fn neighbors(origin: &Point) -> MooreNeighbors { origin.neighbors_moore() }
+        fn neighbor_dist() -> usize { 1 }
+        fn point_order(a: &Point, b: &Point) -> Ordering { a.cmp_reading_order(*b) }
+    }
+
+    struct HexHeuristic;
+
+    impl Heuristic for HexHeuristic {
+        type Item = HexPoint;
+        type Score = usize;
+
+        fn score(from: &HexPoint, to: &HexPoint) -> usize { from.hex_dist_to(*to) }
+        fn zero_score() -> usize { 0 }
+        fn infinity_score() -> usize { usize::MAX }
+    }
+
+    struct HexWorld;
+
+    impl World for HexWorld {
+        type Point = HexPoint;
+        type Score = usize;
+        type Neighbors = HexNeighbors;
+        type Heuristic = HexHeuristic;
+
+        fn neighbors(origin: &HexPoint) -> HexNeighbors { origin.neighbors_reading_order() }
+        fn neighbor_dist() -> usize { 1 }
+        fn point_order(a: &HexPoint, b: &HexPoint) -> Ordering { a.cmp_reading_order(*b) }
+    }
+
     fn load_test_area(map: &str) -> Vec<Point> {
         let mut points = Vec::new();
 
@@ -230,4 +278,41 @@ mod test {
         assert_eq!(Point::new(2, 1), path[2]);
         assert_eq!(Point::new(3, 1), path[3]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn finds_diagonal_path() {
+        let area = load_test_area(r"
+            #####
+            #  ##
+            ## ##
+            ##  #
+            #####");
+
+        let mut pathfinder: Pathfinder<MooreWorld> = Pathfinder::new();
+        let tile_pred = |p: &Point| area.contains(p);
+
+        let mut path = Vec::new();
+        let found = pathfinder.find_path(Point::new(1, 1), Point::new(3, 3), tile_pred, &mut path);
+
+        assert!(found);
+        assert_eq!(3, path.len(), "path should have length 3, found path: {:?}", path);
+        assert_eq!(Point::new(2, 2), path[1]);
+    }
+
+    #[test]
+    fn finds_hex_path_around_obstacle() {
+        let origin = HexPoint::new(0, 0);
+        let dest = HexPoint::new(2, 0);
+        let blocked = HexPoint::new(1, 0);
+
+        let mut pathfinder: Pathfinder<HexWorld> = Pathfinder::new();
+        let tile_pred = |p: &HexPoint| *p != blocked && p.hex_dist_to(origin) <= 3;
+
+        let mut path = Vec::new();
+        let found = pathfinder.find_path(origin, dest, tile_pred, &mut path);
+
+        assert!(found);
+        assert_eq!(4, path.len(), "path should have length 4, found path: {:?}", path);
+        assert!(!path.contains(&blocked));
+    }
+}
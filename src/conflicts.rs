@@ -0,0 +1,171 @@
+#![allow(unused)]
+
+use {
+    crate::point::BoundingBox,
+    std::io::{self, Write},
+};
+
+/// Two rectangles which overlap, `a` being the one earlier in the list
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Conflict {
+    pub a: usize,
+    pub b: usize,
+    pub overlap: BoundingBox,
+}
+
+/// Which rectangles overlap each other, and the groups of rectangles connected by overlaps
+pub struct ConflictGraph {
+    pub conflicts: Vec<Conflict>,
+    /// Groups of two or more rectangles where each one overlaps at least one other in the group,
+    /// ordered by their first rectangle
+    pub groups: Vec<Vec<usize>>,
+}
+
+impl ConflictGraph {
+    pub fn new(rects: &[BoundingBox]) -> Self {
+        // sweep from left to right, so each rectangle only needs to be checked against the ones
+        // which start before it ends
+        let mut by_left: Vec<usize> = (0..rects.len()).collect();
+        by_left.sort_by_key(|i| rects[*i].min().x);
+
+        let mut conflicts = Vec::new();
+        for (n, &i) in by_left.iter().enumerate() {
+            let right = rects[i].max().x;
+
+            for &j in by_left[n + 1..].iter().take_while(|j| rects[**j].min().x <= right) {
+                if let Some(overlap) = rects[i].intersection(&rects[j]) {
+                    let (a, b) = if i < j { (i, j) } else { (j, i) };
+                    conflicts.push(Conflict { a, b, overlap });
+                }
+            }
+        }
+        conflicts.sort_by_key(|conflict| (conflict.a, conflict.b));
+
+        let mut groups = DisjointSets::new(rects.len());
+        for conflict in &conflicts {
+            groups.join(conflict.a, conflict.b);
+        }
+
+        Self {
+            conflicts,
+            groups: groups.into_groups(),
+        }
+    }
+
+    /// One line per conflict, naming rectangles with `label`
+    pub fn write_csv(&self, out: &mut impl Write, label: impl Fn(usize) -> String) -> io::Result<()> {
+        writeln!(out, "a,b,x,y,width,height")?;
+
+        for conflict in &self.conflicts {
+            let overlap = conflict.overlap;
+            writeln!(out, "{},{},{},{},{},{}",
+                     label(conflict.a), label(conflict.b),
+                     overlap.min().x, overlap.min().y, overlap.width(), overlap.height())?;
+        }
+
+        Ok(())
+    }
+
+    /// An undirected Graphviz graph with an edge for each conflict, labelled with the overlap
+    pub fn write_dot(&self, out: &mut impl Write, label: impl Fn(usize) -> String) -> io::Result<()> {
+        writeln!(out, "graph conflicts {{")?;
+
+        for conflict in &self.conflicts {
+            let overlap = conflict.overlap;
+            writeln!(out, "    \"{}\" -- \"{}\" [label=\"{}x{} at {},{}\"];",
+                     label(conflict.a), label(conflict.b),
+                     overlap.width(), overlap.height(), overlap.min().x, overlap.min().y)?;
+        }
+
+        writeln!(out, "}}")
+    }
+}
+
+/// Union-find over `0..n`
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(n: usize) -> Self {
+        Self {
+            parents: (0..n).collect(),
+        }
+    }
+
+    fn root(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            // point at the grandparent on the way up, which keeps the trees shallow
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    fn join(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.root(a), self.root(b));
+        if a != b {
+            self.parents[usize::max(a, b)] = usize::min(a, b);
+        }
+    }
+
+    /// The sets with more than one member, ordered by their first member
+    fn into_groups(mut self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = vec![Vec::new(); self.parents.len()];
+        for i in 0..self.parents.len() {
+            let root = self.root(i);
+            groups[root].push(i);
+        }
+
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::point::Point,
+    };
+
+    fn rect(x: isize, y: isize, width: isize, height: isize) -> BoundingBox {
+        BoundingBox::new(Point::new(x, y), Point::new(x + width - 1, y + height - 1))
+    }
+
+    #[test]
+    fn finds_overlaps_and_groups() {
+        let rects = [
+            rect(1, 3, 4, 4),
+            rect(3, 1, 4, 4),
+            rect(5, 5, 2, 2),
+            rect(10, 0, 2, 2),
+            rect(0, 0, 1, 1),
+            rect(11, 1, 5, 5),
+            rect(6, 4, 1, 1),
+        ];
+        let graph = ConflictGraph::new(&rects);
+
+        let pairs: Vec<_> = graph.conflicts.iter().map(|c| (c.a, c.b)).collect();
+        assert_eq!(vec![(0, 1), (1, 6), (3, 5)], pairs);
+        assert_eq!(rect(3, 3, 2, 2), graph.conflicts[0].overlap);
+        assert_eq!(rect(11, 1, 1, 1), graph.conflicts[2].overlap);
+
+        assert_eq!(vec![vec![0, 1, 6], vec![3, 5]], graph.groups);
+    }
+
+    #[test]
+    fn exports() {
+        let graph = ConflictGraph::new(&[rect(1, 3, 4, 4), rect(3, 1, 4, 4)]);
+        let label = |i: usize| format!("#{}", i + 1);
+
+        let mut csv = Vec::new();
+        graph.write_csv(&mut csv, label).unwrap();
+        assert_eq!("a,b,x,y,width,height\n#1,#2,3,3,2,2\n", String::from_utf8(csv).unwrap());
+
+        let mut dot = Vec::new();
+        graph.write_dot(&mut dot, label).unwrap();
+        assert_eq!("graph conflicts {\n    \"#1\" -- \"#2\" [label=\"2x2 at 3,3\"];\n}\n",
+                   String::from_utf8(dot).unwrap());
+    }
+}
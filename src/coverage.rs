@@ -0,0 +1,181 @@
+#![allow(unused)]
+
+use {
+    crate::{
+        point::{BoundingBox, Grid, Point},
+        summed_area::SummedAreaTable,
+    },
+};
+
+/// How many times each part of a plane is covered by a set of rectangles.
+///
+/// Only the edges of the rectangles matter, so the plane is cut into a grid of cells along
+/// them and coverage is counted per cell instead of per point. The cost depends on the number
+/// of rectangles, not on how big they are.
+pub struct Coverage {
+    rects: Vec<BoundingBox>,
+
+    // the cell at column i covers xs[i] until xs[i + 1], and likewise for rows
+    xs: Vec<isize>,
+    ys: Vec<isize>,
+
+    counts: Grid<usize>,
+    overlap_areas: SummedAreaTable,
+}
+
+impl Coverage {
+    pub fn new(rects: &[BoundingBox]) -> Self {
+        let edges = |edge: fn(&BoundingBox) -> (isize, isize)| {
+            let mut edges: Vec<_> = rects.iter()
+                .flat_map(|rect| {
+                    let (start, end) = edge(rect);
+                    vec![start, end + 1]
+                })
+                .collect();
+            edges.sort();
+            edges.dedup();
+            edges
+        };
+
+        let xs = edges(|rect| (rect.min().x, rect.max().x));
+        let ys = edges(|rect| (rect.min().y, rect.max().y));
+
+        let width = xs.len().saturating_sub(1);
+        let height = ys.len().saturating_sub(1);
+
+        // add each rectangle at its top left corner and take it away again past its right and
+        // bottom edges, so the running sum over each row and column gives the count of each cell
+        let mut diffs = Grid::new(width + 1, height + 1, 0isize);
+        for rect in rects {
+            let cells = cell_range(&xs, &ys, *rect);
+            let (min, end) = (cells.min(), cells.max() + Point::new(1, 1));
+
+            diffs[min] += 1;
+            diffs[Point::new(end.x, min.y)] -= 1;
+            diffs[Point::new(min.x, end.y)] -= 1;
+            diffs[end] += 1;
+        }
+
+        let mut counts = Grid::new(width, height, 0usize);
+        let mut above = vec![0isize; width];
+        for y in 0..height {
+            let mut left = 0;
+            for x in 0..width {
+                left += diffs[Point::new(x as isize, y as isize)];
+                above[x] += left;
+                counts[Point::new(x as isize, y as isize)] = above[x] as usize;
+            }
+        }
+
+        let overlap_areas = SummedAreaTable::new(&Grid::from_fn(width, height, |pos| {
+            if counts[pos] > 1 {
+                cell_area(&xs, &ys, pos) as isize
+            } else {
+                0
+            }
+        }));
+
+        Self {
+            rects: rects.to_vec(),
+            xs,
+            ys,
+            counts,
+            overlap_areas,
+        }
+    }
+
+    /// The area covered by at least `n` rectangles
+    pub fn area_covered(&self, n: usize) -> usize {
+        self.counts.iter()
+            .filter(|(_, count)| **count >= n)
+            .map(|(pos, _)| cell_area(&self.xs, &self.ys, pos))
+            .sum()
+    }
+
+    /// The area covered by more than one rectangle
+    pub fn overlap_area(&self) -> usize {
+        self.area_covered(2)
+    }
+
+    /// The area of rectangle `i` which is also covered by another rectangle
+    pub fn rect_overlap_area(&self, i: usize) -> usize {
+        let cells = cell_range(&self.xs, &self.ys, self.rects[i]);
+        self.overlap_areas.sum(cells) as usize
+    }
+
+    /// The rectangles which don't overlap any other, in order
+    pub fn isolated(&self) -> impl Iterator<Item=usize> + '_ {
+        (0..self.rects.len()).filter(move |i| self.rect_overlap_area(*i) == 0)
+    }
+}
+
+/// The cells covered by `rect`
+fn cell_range(xs: &[isize], ys: &[isize], rect: BoundingBox) -> BoundingBox {
+    let index = |edges: &[isize], edge: isize| {
+        edges.binary_search(&edge).expect("edges must include every rectangle edge") as isize
+    };
+
+    let min = Point::new(index(xs, rect.min().x), index(ys, rect.min().y));
+    let end = Point::new(index(xs, rect.max().x + 1), index(ys, rect.max().y + 1));
+
+    BoundingBox::new(min, end - Point::new(1, 1))
+}
+
+fn cell_area(xs: &[isize], ys: &[isize], cell: Point) -> usize {
+    let (x, y) = (cell.x as usize, cell.y as usize);
+    ((xs[x + 1] - xs[x]) * (ys[y + 1] - ys[y])) as usize
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rect(x: isize, y: isize, width: isize, height: isize) -> BoundingBox {
+        BoundingBox::new(Point::new(x, y), Point::new(x + width - 1, y + height - 1))
+    }
+
+    #[test]
+    fn puzzle_example() {
+        let coverage = Coverage::new(&[rect(1, 3, 4, 4), rect(3, 1, 4, 4), rect(5, 5, 2, 2)]);
+
+        assert_eq!(4, coverage.overlap_area());
+        assert_eq!(4, coverage.rect_overlap_area(0));
+        assert_eq!(4, coverage.rect_overlap_area(1));
+        assert_eq!(vec![2], coverage.isolated().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn matches_counting_every_point() {
+        let rects = [
+            rect(0, 0, 10, 10),
+            rect(5, 5, 10, 3),
+            rect(-4, 2, 6, 20),
+            rect(6, 6, 1, 1),
+            rect(20, 20, 3, 3),
+            rect(0, 0, 10, 10),
+        ];
+        let coverage = Coverage::new(&rects);
+
+        let bounds = rects.iter().fold(rects[0], |bounds, rect| bounds.union(rect));
+        let count_at = |pos: Point| rects.iter().filter(|rect| rect.contains(pos)).count();
+
+        for n in 1..=4 {
+            let expected = bounds.coords().filter(|pos| count_at(*pos) >= n).count();
+            assert_eq!(expected, coverage.area_covered(n), "covered {} times", n);
+        }
+
+        for (i, rect) in rects.iter().enumerate() {
+            let expected = rect.coords().filter(|pos| count_at(*pos) > 1).count();
+            assert_eq!(expected, coverage.rect_overlap_area(i), "rect {}", i);
+        }
+
+        assert_eq!(vec![4], coverage.isolated().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn empty() {
+        let coverage = Coverage::new(&[]);
+        assert_eq!(0, coverage.overlap_area());
+        assert_eq!(0, coverage.isolated().count());
+    }
+}
@@ -1,9 +1,85 @@
 use {
     std::{
-        collections::HashSet,
+        collections::{HashMap, HashSet},
     },
 };
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct FirstRepeat {
+    frequency: i64,
+    /// How many full passes over the changes were made before the repeat
+    iteration: usize,
+    /// The change which produced the repeated frequency
+    change_index: usize,
+}
+
+/// Finds the first frequency reached twice when applying `changes` over and over, starting from
+/// 0 (which counts as reached), or `None` if no frequency is ever reached twice.
+///
+/// After `k` full passes, the frequency reached by the change before `i` is `prefix[i] + k *
+/// drift`, so every frequency a prefix sum reaches lies on the same line modulo `drift`. A prefix
+/// sum can only catch up with another one on the same line, and the first one it catches is the
+/// next one along in the direction of the drift.
+fn first_repeat(changes: &[i64]) -> Option<FirstRepeat> {
+    let n = changes.len();
+
+    // prefix[i] is the frequency before change i is applied in the first pass
+    let prefix: Vec<i64> = changes.iter()
+        .scan(0, |total, change| {
+            let before = *total;
+            *total += change;
+            Some(before)
+        })
+        .collect();
+
+    let drift: i64 = changes.iter().sum();
+
+    // `time` counts changes applied, so the frequency at time t was produced by change t - 1
+    let repeat_at = |time: usize, frequency: i64| FirstRepeat {
+        frequency,
+        iteration: (time - 1) / n,
+        change_index: (time - 1) % n,
+    };
+
+    // a repeat during the first pass comes before anything caused by the drift
+    let mut seen = HashSet::new();
+    for (time, &frequency) in prefix.iter().enumerate() {
+        if !seen.insert(frequency) {
+            return Some(repeat_at(time, frequency));
+        }
+    }
+
+    if n == 0 {
+        return None;
+    }
+
+    if drift == 0 {
+        // every pass ends where it started
+        return Some(repeat_at(n, 0));
+    }
+
+    let mut lines: HashMap<i64, Vec<usize>> = HashMap::new();
+    for (i, &frequency) in prefix.iter().enumerate() {
+        lines.entry(frequency.rem_euclid(drift)).or_default().push(i);
+    }
+
+    lines.values_mut()
+        .flat_map(|line| {
+            // order each line in the direction of the drift
+            line.sort_by_key(|&i| prefix[i] * drift.signum());
+
+            line.windows(2)
+                .map(|pair| {
+                    let (behind, ahead) = (pair[0], pair[1]);
+                    let passes = ((prefix[ahead] - prefix[behind]) / drift) as usize;
+                    (passes * n + behind, prefix[ahead])
+                })
+                .collect::<Vec<_>>()
+        })
+        .min_by_key(|(time, _)| *time)
+        .map(|(time, frequency)| repeat_at(time, frequency))
+}
+
 fn main() {
     let input = include_str!("day_1.txt");
     let values: Vec<_> = input.lines()
@@ -13,17 +89,42 @@ fn main() {
 
     println!("total value: {}", values.iter().sum::<i64>());
 
-    let mut prev_vals = HashSet::new();
-    let mut values_cycle = values.iter().cycle();
+    match first_repeat(&values) {
+        Some(repeat) => {
+            println!("first repeated value: {}", repeat.frequency);
+            println!("  reached in pass {} by change {}", repeat.iteration + 1, repeat.change_index + 1);
+        }
+        None => println!("no value is ever repeated"),
+    }
+}
 
-    let mut total = 0;
-    let first_repeated = loop {
-        total += values_cycle.next().unwrap();
+#[cfg(test)]
+mod test {
+    use super::*;
 
-        if !prev_vals.insert(total) {
-            break total;
-        }
-    };
+    #[test]
+    fn puzzle_examples() {
+        let frequency = |changes: &[i64]| first_repeat(changes).map(|repeat| repeat.frequency);
+
+        assert_eq!(Some(0), frequency(&[1, -1]));
+        assert_eq!(Some(10), frequency(&[3, 3, 4, -2, -4]));
+        assert_eq!(Some(5), frequency(&[-6, 3, 8, 5, -6]));
+        assert_eq!(Some(14), frequency(&[7, 7, -2, -7, -4]));
+    }
+
+    #[test]
+    fn reports_when_the_repeat_happens() {
+        let repeat = FirstRepeat { frequency: 0, iteration: 0, change_index: 1 };
+        assert_eq!(Some(repeat), first_repeat(&[1, -1]));
+
+        // 3, 6, 10, 8, 4, then 7, 10
+        let repeat = FirstRepeat { frequency: 10, iteration: 1, change_index: 1 };
+        assert_eq!(Some(repeat), first_repeat(&[3, 3, 4, -2, -4]));
+    }
 
-    println!("first repeated value: {}", first_repeated);
+    #[test]
+    fn never_repeats() {
+        assert_eq!(None, first_repeat(&[1, 1]));
+        assert_eq!(None, first_repeat(&[]));
+    }
 }
@@ -1,35 +1,42 @@
+mod point;
+mod render;
+mod image;
+mod record;
+mod args;
+
 use {
+    crate::{
+        point::{BoundingBox, Point},
+        render::{Glyph, Renderer},
+        image::{Image, Rgb},
+        record::Recorder,
+    },
     std::{
+        collections::HashSet,
         fmt,
         mem,
     },
     regex::Regex,
 };
 
-#[derive(Eq, PartialEq, Clone)]
-struct Vector2 {
-    x: i32,
-    y: i32,
-}
-
 #[derive(Clone)]
 struct Entry {
-    position: Vector2,
-    velocity: Vector2,
+    position: Point,
+    velocity: Point,
 }
 
 impl Entry {
     fn parse(s: &str, entry_pattern: &Regex) -> Self {
         let groups = entry_pattern.captures(s).unwrap();
         //format: "position=<xx, xx> velocity=<xx, xx>"
-        let pos_x: i32 = groups[1].parse().unwrap();
-        let pos_y: i32 = groups[2].parse().unwrap();
-        let vel_x: i32 = groups[3].parse().unwrap();
-        let vel_y: i32 = groups[4].parse().unwrap();
+        let pos_x: isize = groups[1].parse().unwrap();
+        let pos_y: isize = groups[2].parse().unwrap();
+        let vel_x: isize = groups[3].parse().unwrap();
+        let vel_y: isize = groups[4].parse().unwrap();
 
         Self {
-            position: Vector2 { x: pos_x, y: pos_y },
-            velocity: Vector2 { x: vel_x, y: vel_y },
+            position: Point::new(pos_x, pos_y),
+            velocity: Point::new(vel_x, vel_y),
         }
     }
 }
@@ -44,25 +51,18 @@ impl fmt::Display for Entry {
 
 fn next_state(entries: &[Entry], next: &mut [Entry]) {
     for (i, entry) in entries.iter().enumerate() {
-        next[i].position.x = entry.position.x + entry.velocity.x;
-        next[i].position.y = entry.position.y + entry.velocity.y;
+        next[i].position = entry.position + entry.velocity;
     }
 }
 
-fn bounds(entries: &[Entry]) -> (Vector2, Vector2) {
-    let min_x = entries.iter().fold(0, |min, entry| i32::min(entry.position.x, min));
-    let max_x = entries.iter().fold(0, |max, entry| i32::max(entry.position.x, max));
-    let min_y = entries.iter().fold(0, |min, entry| i32::min(entry.position.y, min));
-    let max_y = entries.iter().fold(0, |max, entry| i32::max(entry.position.y, max));
-
-    (Vector2 { x: min_x, y: min_y }, Vector2 { x: max_x, y: max_y })
+fn bounds(entries: &[Entry]) -> BoundingBox {
+    BoundingBox::of_points(entries.iter().map(|entry| entry.position))
+        .expect("must have at least one entry")
 }
 
-fn bounds_size(entries: &[Entry]) -> i32 {
-    let (min, max) = bounds(entries);
-    let x_dist = (min.x - max.x).abs();
-    let y_dist = (min.y - max.y).abs();
-    x_dist + y_dist
+fn bounds_size(entries: &[Entry]) -> usize {
+    let bounds = bounds(entries);
+    bounds.width() + bounds.height()
 }
 
 fn main() {
@@ -76,12 +76,26 @@ fn main() {
     let mut next = entries.clone();
 
     let mut time = 0;
+    let mut recorder = Recorder::from_args();
 
     loop {
+        if let Some(recorder) = &mut recorder {
+            recorder.record(time, || scene_frame(&entries)).expect("failed to record frame");
+        }
+
         next_state(&entries, &mut next);
         if bounds_size(&next) > bounds_size(&entries) {
             print_scene(&entries);
             println!("time: {}", time);
+
+            if let Some(path) = image::export_path() {
+                scene_image(&entries).save_ppm(&path).expect("failed to save image");
+                println!("saved message to {}", path.display());
+            }
+
+            if let Some(recorder) = &recorder {
+                println!("recorded {} frames to {}", recorder.frames(), recorder.dir().display());
+            }
             break;
         } else {
             mem::swap(&mut entries, &mut next);
@@ -90,20 +104,35 @@ fn main() {
     }
 }
 
+fn render_scene(entries: &[Entry], renderer: Renderer) -> String {
+    let positions: HashSet<_> = entries.iter().map(|entry| entry.position).collect();
+
+    renderer.render_to_string(|pos| {
+        Glyph::new(if positions.contains(&pos) { '#' } else { '.' })
+    })
+}
+
 fn print_scene(entries: &[Entry]) {
-    let (min, max) = bounds(entries);
+    println!("{}", render_scene(entries, Renderer::new(bounds(entries))));
+}
 
-    for y in min.y..=max.y {
-        for x in min.x..=max.x {
-            let entry = entries.iter()
-                .find(|entry| entry.position == Vector2 { x, y });
+/// The middle of the scene, cropped so frames from before the stars converge stay small
+fn scene_frame(entries: &[Entry]) -> String {
+    let bounds = bounds(entries);
+    let center = Point::new((bounds.min().x + bounds.max().x) / 2, (bounds.min().y + bounds.max().y) / 2);
+
+    render_scene(entries, Renderer::new(bounds).viewport(center, 120, 40))
+}
 
-            print!("{}", match entry {
-                Some(_) => '#',
-                None => '.'
-            });
+fn scene_image(entries: &[Entry]) -> Image {
+    let positions: HashSet<_> = entries.iter().map(|entry| entry.position).collect();
+
+    // leave a margin around the message so it's not cut off at the edges of the image
+    Image::render(bounds(entries).expand(2), 6, |pos| {
+        if positions.contains(&pos) {
+            Rgb::new(255, 240, 160)
+        } else {
+            Rgb::new(10, 10, 40)
         }
-        println!();
-    }
-    println!();
-}
\ No newline at end of file
+    })
+}
@@ -1,5 +1,14 @@
+mod point;
+mod summed_area;
+mod image;
+mod args;
+
 use {
-    rayon::prelude::*,
+    crate::{
+        point::{Grid, Point},
+        summed_area::SummedAreaTable,
+        image::{Image, Palette, Rgb},
+    },
 };
 
 const SERIAL: isize = 9445;
@@ -22,21 +31,20 @@ struct Result {
     power_level: isize,
 }
 
-fn index_at(x: usize, y: usize) -> usize {
-    y * GRID_SIZE + x
+fn power_table(grid_size: usize, serial_number: isize) -> SummedAreaTable {
+    let grid = Grid::from_fn(grid_size, grid_size, |point| {
+        power_level(point.x, point.y, serial_number)
+    });
+
+    SummedAreaTable::new(&grid)
 }
 
-fn find_max(grid: &[isize], size: usize) -> Result {
+fn find_max(table: &SummedAreaTable, size: usize) -> Result {
     let mut max = None;
 
-    for y in 0..GRID_SIZE - (size - 1) {
-        for x in 0..GRID_SIZE - (size - 1) {
-            let mut sum = 0;
-            for local_x in 0..size {
-                for local_y in 0..size {
-                    sum += grid[index_at(x + local_x, y + local_y)];
-                }
-            }
+    for y in 0..table.height() - (size - 1) {
+        for x in 0..table.width() - (size - 1) {
+            let sum = table.square_sum(Point::new(x as isize, y as isize), size);
 
             let result = (x, y, sum);
 
@@ -48,29 +56,72 @@ fn find_max(grid: &[isize], size: usize) -> Result {
         }
     }
 
-    let (x, y, power_level) = max.expect(&format!("must have a max level for size {}", size));
+    let (x, y, power_level) = max.unwrap_or_else(|| panic!("must have a max level for size {}", size));
     Result { x, y, power_level, size }
 }
 
+/// The square of any size with the highest total power. Where several squares are tied, the
+/// smallest is chosen.
+fn find_best_square(grid_size: usize, serial_number: isize) -> Result {
+    let table = power_table(grid_size, serial_number);
+
+    let mut best: Option<Result> = None;
+    for size in 1..=grid_size {
+        let result = find_max(&table, size);
+
+        if best.as_ref().map(|best| best.power_level < result.power_level).unwrap_or(true) {
+            best = Some(result);
+        }
+    }
+
+    best.expect("grid must not be empty")
+}
+
+/// Every fuel cell's power level from coldest to hottest, with the best square outlined
+fn power_heatmap(grid_size: usize, serial_number: isize, best: &Result) -> Image {
+    let grid = Grid::from_fn(grid_size, grid_size, |point| {
+        power_level(point.x, point.y, serial_number)
+    });
+
+    // power levels range from -5 to 4
+    let palette = Palette::gradient(Rgb::new(20, 30, 120), Rgb::new(250, 200, 40), 10);
+
+    let (min_x, min_y) = (best.x as isize, best.y as isize);
+    let (max_x, max_y) = (min_x + best.size as isize - 1, min_y + best.size as isize - 1);
+    let on_outline = |p: Point| {
+        let in_x = p.x >= min_x && p.x <= max_x;
+        let in_y = p.y >= min_y && p.y <= max_y;
+        (in_x && (p.y == min_y || p.y == max_y)) || (in_y && (p.x == min_x || p.x == max_x))
+    };
+
+    let bounds = grid.bounds().expect("grid must not be empty");
+    Image::render(bounds, 2, |p| if on_outline(p) {
+        Rgb::WHITE
+    } else {
+        palette.get((grid[p] + 5) as usize)
+    })
+}
+
 fn main() {
     assert_eq!(4, power_level(3, 5, 8), "power at 3, 5 with serial number 8");
     assert_eq!(-5, power_level(122, 79, 57), "power at 122, 79 with serial number 57");
     assert_eq!(0, power_level(217, 196, 39), "power at 217, 196 with serial number 39", );
     assert_eq!(4, power_level(101, 153, 71), "power at 101, 153 with serial number 71");
 
-    let mut grid = [0; GRID_SIZE * GRID_SIZE];
+    let example = find_max(&power_table(GRID_SIZE, 18), 3);
+    assert_eq!((33, 45, 29), (example.x, example.y, example.power_level), "3x3 square with serial number 18");
 
-    for y in 0..GRID_SIZE {
-        for x in 0..GRID_SIZE {
-            grid[index_at(x, y)] = power_level(x as isize, y as isize, SERIAL);
-        }
-    }
+    let example = find_best_square(GRID_SIZE, 18);
+    assert_eq!((90, 269, 16, 113), (example.x, example.y, example.size, example.power_level),
+        "best square with serial number 18");
 
-    let result = (0..300usize).into_par_iter()
-        .map(|size| find_max(&grid, size + 1))
-        .max_by_key(|result| result.power_level)
-        .unwrap();
+    let result = find_best_square(GRID_SIZE, SERIAL);
 
     println!("highest power level is at {}, {} size {} with level {}",
         result.x, result.y, result.size, result.power_level);
-}
\ No newline at end of file
+
+    if let Some(path) = image::export_path() {
+        power_heatmap(GRID_SIZE, SERIAL, &result).save_ppm(&path).expect("failed to save image");
+        println!("saved power heatmap to {}", path.display());
+    }
+}
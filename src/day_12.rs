@@ -1,4 +1,18 @@
-use std::collections::{HashMap, VecDeque};
+mod point;
+mod render;
+mod record;
+mod stepper;
+mod args;
+
+use {
+    crate::{
+        point::{BoundingBox, Point},
+        render::{Glyph, Renderer},
+        record::Recorder,
+        stepper::{Simulation, Stepper},
+    },
+    std::collections::{HashMap, VecDeque},
+};
 
 use self::Pot::*;
 
@@ -100,6 +114,30 @@ impl Generation {
         new_gen
     }
 
+    /// The pots as a single row, with pot 0 at x = 0
+    fn bounds(&self) -> BoundingBox {
+        let first = -self.zero_index;
+        let last = first + self.pots.len() as isize - 1;
+        BoundingBox::new(Point::new(first, 0), Point::new(last, 0))
+    }
+
+    fn glyph_at(&self, pos: Point) -> Glyph {
+        let index = pos.x + self.zero_index;
+        if pos.y != 0 || index < 0 || index >= self.pots.len() as isize {
+            return Glyph::new(' ');
+        }
+
+        match self.pots[index as usize] {
+            Plant => Glyph::new('#'),
+            NoPlant => Glyph::new('.'),
+        }
+    }
+
+    /// The row of pots as `#` and `.`
+    fn render(&self) -> String {
+        Renderer::new(self.bounds()).render_to_string(|pos| self.glyph_at(pos))
+    }
+
     fn plant_labels(&self) -> impl Iterator<Item=isize> + '_ {
         self.pots.iter().enumerate()
             .filter_map(move |(i, pot)| match pot {
@@ -109,6 +147,69 @@ impl Generation {
     }
 }
 
+/// The generations for the stepper, which stops when the plant sum starts or stops growing at a
+/// steady rate
+#[derive(Clone)]
+struct Pots<'a> {
+    generation: Generation,
+    rules: &'a Rules,
+    last_sum_dist: Option<isize>,
+    steady: bool,
+}
+
+impl Pots<'_> {
+    fn plant_sum(&self) -> isize {
+        self.generation.plant_labels().sum()
+    }
+}
+
+impl Simulation for Pots<'_> {
+    fn bounds(&self) -> Option<BoundingBox> {
+        Some(self.generation.bounds())
+    }
+
+    fn glyph_at(&self, pos: Point) -> Glyph {
+        self.generation.glyph_at(pos)
+    }
+
+    fn advance(&mut self) -> Option<String> {
+        let last_sum = self.plant_sum();
+        self.generation = self.generation.next(self.rules);
+
+        let sum_dist = self.plant_sum() - last_sum;
+        let steady = self.last_sum_dist == Some(sum_dist);
+        self.last_sum_dist = Some(sum_dist);
+
+        let event = match (self.steady, steady) {
+            (false, true) => Some(format!("plant sum now grows steadily by {}", sum_dist)),
+            (true, false) => Some(format!("plant sum stopped growing steadily, grew by {}", sum_dist)),
+            _ => None,
+        };
+        self.steady = steady;
+
+        event
+    }
+
+    fn is_finished(&self) -> bool {
+        false
+    }
+
+    fn inspect(&self, pos: Point) -> Option<String> {
+        let index = pos.x + self.generation.zero_index;
+        if pos.y != 0 || index < 0 || index >= self.generation.pots.len() as isize {
+            return None;
+        }
+
+        let neighborhood = self.generation.pots_at(index);
+        let pattern: String = neighborhood.iter()
+            .map(|pot| if *pot == Plant { '#' } else { '.' })
+            .collect();
+
+        Some(format!("pot {}: {:?}, {} => {:?} next", pos.x, self.generation.pots[index as usize],
+                     pattern, self.rules[&neighborhood]))
+    }
+}
+
 const GENERATIONS: usize = 20;
 
 fn main() {
@@ -122,10 +223,19 @@ fn main() {
         zero_index: 0,
     };
 
-    let result = (0..GENERATIONS).fold(initial.clone(), |last_gen, _gen| {
-        let next_gen = last_gen.next(&rules);
-        next_gen
-    });
+    if stepper::requested() {
+        let pots = Pots {
+            generation: initial,
+            rules: &rules,
+            last_sum_dist: None,
+            steady: false,
+        };
+
+        Stepper::new(pots).run_terminal().expect("failed to run stepper");
+        return;
+    }
+
+    let result = (0..GENERATIONS).fold(initial.clone(), |last_gen, _gen| last_gen.next(&rules));
 
     println!(
         "number of plants at gen {}: {} (label sum: {})",
@@ -134,9 +244,14 @@ fn main() {
         result.plant_labels().sum::<isize>()
     );
 
+    let mut recorder = Recorder::from_args();
+
     // iterate until we find a stable sum
     let (stable_gen, stable_sum, stable_dist) = {
         let mut next_gen = initial;
+        if let Some(recorder) = &mut recorder {
+            recorder.record(0, || next_gen.render()).expect("failed to record frame");
+        }
 
         let mut last_sum = next_gen.plant_labels().sum::<isize>();
         let mut last_sum_dist = 0;
@@ -144,6 +259,10 @@ fn main() {
 
         loop {
             next_gen = next_gen.next(&rules);
+            if let Some(recorder) = &mut recorder {
+                recorder.record(gen as usize, || next_gen.render()).expect("failed to record frame");
+            }
+
             let sum = next_gen.plant_labels().sum::<isize>();
             let sum_dist = sum - last_sum;
 
@@ -158,6 +277,11 @@ fn main() {
     };
 
     println!("stable sum dist at gen {}: {}", stable_gen, stable_dist);
+
+    if let Some(recorder) = &recorder {
+        println!("recorded {} frames to {}", recorder.frames(), recorder.dir().display());
+    }
+
     let rest_gens = 50_000_000_000 - stable_gen;
     println!("sum at gen 50bn: {}", stable_sum + rest_gens * stable_dist);
 }
@@ -1,99 +1,66 @@
+mod point;
+mod render;
+mod image;
+mod record;
+mod stepper;
+mod args;
+
 use {
+    crate::{
+        point::{BoundingBox, Direction, Grid, Point},
+        render::{Glyph, Renderer},
+        image::{Image, Rgb},
+        record::Recorder,
+        stepper::{Simulation, Stepper},
+    },
     std::{
         fmt,
-        collections::HashMap,
-        cmp::Ordering,
-        ops::Add,
     },
 };
 
-#[derive(Debug, Copy, Clone)]
-enum CartFacing {
-    North,
-    South,
-    East,
-    West,
-}
-
-impl CartFacing {
-    fn parse(c: char) -> Option<CartFacing> {
-        match c {
-            '^' => Some(CartFacing::North),
-            'v' => Some(CartFacing::South),
-            '<' => Some(CartFacing::West),
-            '>' => Some(CartFacing::East),
-            _ => None,
-        }
-    }
-
-    fn underlying_section(&self) -> Section {
-        match self {
-            CartFacing::North | CartFacing::South => Section::Vertical,
-            CartFacing::East | CartFacing::West => Section::Horizontal,
-        }
-    }
-
-    fn velocity(&self) -> Coord {
-        match self {
-            CartFacing::North => Coord { x: 0, y: -1 },
-            CartFacing::South => Coord { x: 0, y: 1 },
-            CartFacing::East => Coord { x: 1, y: 0 },
-            CartFacing::West => Coord { x: -1, y: 0 },
-        }
+fn parse_facing(c: char) -> Option<Direction> {
+    match c {
+        '^' => Some(Direction::North),
+        'v' => Some(Direction::South),
+        '<' => Some(Direction::West),
+        '>' => Some(Direction::East),
+        _ => None,
     }
+}
 
-    fn turn(&self, turn: Turn) -> Self {
-        match turn {
-            Turn::Left => match self {
-                CartFacing::North => CartFacing::West,
-                CartFacing::West => CartFacing::South,
-                CartFacing::South => CartFacing::East,
-                CartFacing::East => CartFacing::North,
-            },
-            Turn::Right => match self {
-                CartFacing::North => CartFacing::East,
-                CartFacing::East => CartFacing::South,
-                CartFacing::South => CartFacing::West,
-                CartFacing::West => CartFacing::North,
-            },
-            Turn::Straight => self.clone(),
-        }
+fn facing_char(facing: Direction) -> char {
+    match facing {
+        Direction::North => '^',
+        Direction::South => 'v',
+        Direction::East => '>',
+        Direction::West => '<',
     }
 }
 
-impl fmt::Display for CartFacing {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", match self {
-            CartFacing::North => '^',
-            CartFacing::South => 'v',
-            CartFacing::East => '>',
-            CartFacing::West => '<',
-        })
+fn underlying_section(facing: Direction) -> Section {
+    if facing.is_vertical() {
+        Section::Vertical
+    } else {
+        Section::Horizontal
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Curve {
     Right,
     Left
 }
 
 impl Curve {
-    fn turn(&self, from_facing: CartFacing) -> Turn {
-        match self {
-            Curve::Right => match from_facing {
-                CartFacing::North | CartFacing::South => Turn::Right,
-                CartFacing::East | CartFacing::West => Turn::Left,
-            }
-            Curve::Left => match from_facing {
-                CartFacing::East | CartFacing::West => Turn::Right,
-                CartFacing::North | CartFacing::South => Turn::Left,
-            }
+    fn turn(&self, from_facing: Direction) -> Turn {
+        match (self, from_facing.is_vertical()) {
+            (Curve::Right, true) | (Curve::Left, false) => Turn::Right,
+            (Curve::Right, false) | (Curve::Left, true) => Turn::Left,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Section {
     Vertical,
     Horizontal,
@@ -101,19 +68,17 @@ enum Section {
     Curve(Curve),
 }
 
-impl fmt::Display for Section {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", match self {
+impl Section {
+    fn to_char(&self) -> char {
+        match self {
             Section::Vertical => '|',
             Section::Horizontal => '-',
             Section::Intersection => '+',
             Section::Curve(Curve::Right) => '/',
             Section::Curve(Curve::Left) => '\\',
-        })
+        }
     }
-}
 
-impl Section {
     fn parse(c: char) -> Option<Section> {
         match c {
             '|' => Some(Section::Vertical),
@@ -126,34 +91,32 @@ impl Section {
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash)]
-struct Coord {
-    x: isize,
-    y: isize,
-}
-
-impl Add for Coord {
-    type Output = Self;
-    fn add(self, rhs: Self) -> Self {
-        Self { x: self.x + rhs.x, y: self.y + rhs.y }
-    }
-}
-
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone)]
 enum Turn {
     Left,
     Straight,
     Right,
 }
 
+impl Turn {
+    fn apply(self, facing: Direction) -> Direction {
+        match self {
+            Turn::Left => facing.turn_left(),
+            Turn::Straight => facing,
+            Turn::Right => facing.turn_right(),
+        }
+    }
+}
+
+#[derive(Clone)]
 struct Cart {
-    pos: Coord,
-    facing: CartFacing,
+    pos: Point,
+    facing: Direction,
     last_turn: Turn,
 }
 
 impl Cart {
-    fn new(pos: Coord, facing: CartFacing) -> Self {
+    fn new(pos: Point, facing: Direction) -> Self {
         Self {
             pos,
             facing,
@@ -172,34 +135,49 @@ impl Cart {
     }
 }
 
+#[derive(Clone)]
 struct Track {
-    sections: HashMap<Coord, Section>,
+    sections: Grid<Option<Section>>,
     carts: Vec<Cart>,
-    first_crash: Option<Coord>,
+    first_crash: Option<Point>,
 }
 
-impl fmt::Display for Track {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let min_x = self.sections.keys().map(|pos| pos.x).min().unwrap();
-        let min_y = self.sections.keys().map(|pos| pos.y).min().unwrap();
-        let max_x = self.sections.keys().map(|pos| pos.x).max().unwrap();
-        let max_y = self.sections.keys().map(|pos| pos.y).max().unwrap();
-
-        for y in min_y..=max_y {
-            for x in min_x..=max_x {
-                let pos = Coord { x, y };
-                match self.carts.iter().find(|cart| cart.pos == pos) {
-                    Some(cart) => write!(f, "{}", cart.facing)?,
-                    None => match self.sections.get(&Coord { x, y }) {
-                        Some(section) => write!(f, "{}", section)?,
-                        None => write!(f, " ")?,
-                    }
+impl Track {
+    fn glyph_at(&self, pos: Point) -> Glyph {
+        match self.carts.iter().find(|cart| cart.pos == pos) {
+            Some(cart) => Glyph::new(facing_char(cart.facing)),
+            None => match self.sections.get(pos) {
+                Some(Some(section)) => Glyph::new(section.to_char()),
+                _ => Glyph::new(' '),
+            },
+        }
+    }
+}
+
+impl Track {
+    fn to_image(&self) -> Option<Image> {
+        let bounds = self.sections.bounds()?;
+
+        Some(Image::render(bounds, 4, |pos| {
+            if self.carts.iter().any(|cart| cart.pos == pos) {
+                Rgb::new(230, 60, 40)
+            } else {
+                match self.sections.get(pos) {
+                    Some(Some(Section::Intersection)) => Rgb::new(250, 210, 60),
+                    Some(Some(_)) => Rgb::new(160, 160, 170),
+                    _ => Rgb::new(20, 20, 30),
                 }
             }
-            writeln!(f)?;
-        }
+        }))
+    }
+}
 
-        Ok(())
+impl fmt::Display for Track {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.sections.bounds() {
+            Some(bounds) => Renderer::new(bounds).render(f, |pos| self.glyph_at(pos)),
+            None => Ok(()),
+        }
     }
 }
 
@@ -211,29 +189,17 @@ enum MoveResult {
 
 impl Track {
     fn parse(s: &str) -> Self {
-        let tiles: Vec<_> = s.lines().enumerate()
-            .flat_map(|(y, line)| {
-                line.chars().enumerate().filter_map(move |(x, c)| {
-                    let cart_facing = CartFacing::parse(c);
-                    let section = cart_facing.as_ref()
-                        .map(|c| c.underlying_section())
-                        .or_else(|| Section::parse(c));
-                    let pos = Coord { x: x as isize, y: y as isize };
-
-                    section.map(|section| (pos, section, cart_facing))
-                })
-            })
-            .collect();
-
-        let carts = tiles.iter()
-            .filter_map(|(pos, _, facing)| {
-                facing.map(|facing| Cart::new(*pos, facing))
-            })
-            .collect();
+        let mut carts = Vec::new();
 
-        let sections = tiles.into_iter()
-            .map(|(pos, section, _)| (pos, section))
-            .collect();
+        let sections = Grid::parse(s, |pos, c| {
+            match parse_facing(c) {
+                Some(facing) => {
+                    carts.push(Cart::new(pos, facing));
+                    Some(underlying_section(facing))
+                }
+                None => Section::parse(c),
+            }
+        });
 
         Self {
             sections,
@@ -243,7 +209,8 @@ impl Track {
     }
 
     fn move_cart(&mut self, i: usize) -> MoveResult {
-        self.carts[i].pos = self.carts[i].pos + self.carts[i].facing.velocity();
+        let facing = self.carts[i].facing;
+        self.carts[i].pos += facing;
 
         for j in (0..self.carts.len()).filter(|j| *j != i) {
             if self.carts[i].pos == self.carts[j].pos {
@@ -251,8 +218,9 @@ impl Track {
             }
         }
 
-        let mut cart = &mut self.carts[i];
-        let section = self.sections.get(&cart.pos)
+        let cart = &mut self.carts[i];
+        let section = self.sections.get(cart.pos)
+            .and_then(Option::as_ref)
             .unwrap_or_else(|| panic!("missing section at {},{}", cart.pos.x, cart.pos.y));
 
         let turn = match section {
@@ -260,24 +228,22 @@ impl Track {
             Section::Intersection => cart.intersection_turn(),
             Section::Vertical | Section::Horizontal => Turn::Straight,
         };
-        cart.facing = cart.facing.turn(turn);
+        cart.facing = turn.apply(cart.facing);
 
         MoveResult::Ok
     }
 
-    fn tick(&mut self) {
-        self.carts.sort_by(|cart_a, cart_b| {
-            match cart_a.pos.y.cmp(&cart_b.pos.y) {
-                Ordering::Equal => cart_a.pos.x.cmp(&cart_b.pos.x),
-                y_ord => y_ord,
-            }
-        });
+    /// Moves every cart once, returning where any crashes happened
+    fn tick(&mut self) -> Vec<Point> {
+        let mut crashes = Vec::new();
+        self.carts.sort_by(|cart_a, cart_b| cart_a.pos.cmp_reading_order(cart_b.pos));
 
         let mut i = 0;
         while i < self.carts.len() {
             match self.move_cart(i) {
                 MoveResult::Ok => i += 1,
                 MoveResult::Crash(j) => {
+                    crashes.push(self.carts[i].pos);
                     if self.first_crash.is_none() {
                         self.first_crash = Some(self.carts[i].pos);
                     }
@@ -294,6 +260,46 @@ impl Track {
                 }
             }
         }
+
+        crashes
+    }
+}
+
+impl Simulation for Track {
+    fn bounds(&self) -> Option<BoundingBox> {
+        self.sections.bounds()
+    }
+
+    fn glyph_at(&self, pos: Point) -> Glyph {
+        Track::glyph_at(self, pos)
+    }
+
+    fn advance(&mut self) -> Option<String> {
+        let crashes: Vec<_> = self.tick().iter()
+            .map(|pos| format!("{},{}", pos.x, pos.y))
+            .collect();
+
+        if crashes.is_empty() {
+            None
+        } else {
+            Some(format!("crash at {} ({} carts left)", crashes.join(" and "), self.carts.len()))
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.carts.len() <= 1
+    }
+
+    fn inspect(&self, pos: Point) -> Option<String> {
+        if let Some(cart) = self.carts.iter().find(|cart| cart.pos == pos) {
+            return Some(format!("cart facing {:?}, last turned {:?} at an intersection",
+                                cart.facing, cart.last_turn));
+        }
+
+        match self.sections.get(pos) {
+            Some(Some(section)) => Some(format!("{:?} track", section)),
+            _ => None,
+        }
     }
 }
 
@@ -301,10 +307,21 @@ fn main() {
     let input = include_str!("day_13.txt");
     let mut track = Track::parse(input);
 
-    for _time in 0.. {
+    if stepper::requested() {
+        Stepper::new(track).run_terminal().expect("failed to run stepper");
+        return;
+    }
+
+    let mut recorder = Recorder::from_args();
+
+    for time in 0.. {
+        if let Some(recorder) = &mut recorder {
+            recorder.record(time, || track.to_string()).expect("failed to record frame");
+        }
+
         track.tick();
 
-        if track.carts.len() == 0 {
+        if track.carts.is_empty() {
             println!("all carts crashed");
             break;
         }
@@ -318,4 +335,14 @@ fn main() {
 
     let first_crash = track.first_crash.unwrap();
     println!("first crash at {},{}", first_crash.x, first_crash.y);
+
+    if let Some(path) = image::export_path() {
+        let image = track.to_image().expect("track must not be empty");
+        image.save_ppm(&path).expect("failed to save image");
+        println!("saved final state to {}", path.display());
+    }
+
+    if let Some(recorder) = &recorder {
+        println!("recorded {} frames to {}", recorder.frames(), recorder.dir().display());
+    }
 }
\ No newline at end of file
@@ -1,12 +1,25 @@
 mod point;
 mod astar;
+mod flood_fill;
+mod render;
+mod image;
+mod record;
+mod stepper;
+mod args;
 
 use {
     crate::{
         astar::Pathfinder,
+        flood_fill::flood_fill,
+        render::{Color, Glyph, Renderer},
+        image::{Image, Rgb},
+        record::Recorder,
+        stepper::{Simulation, Stepper},
         point::{
+            BoundingBox,
             Point,
             Neighbors,
+            Grid,
         },
     },
     std::{
@@ -17,7 +30,6 @@ use {
             HashMap,
         },
         time::Instant,
-        usize,
     },
     rayon::prelude::*,
 };
@@ -96,20 +108,9 @@ enum Tile {
     Blocked,
 }
 
-impl fmt::Display for Tile {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Tile::Empty => write!(f, "."),
-            Tile::Blocked => write!(f, "#"),
-        }
-    }
-}
-
 #[derive(Clone)]
 struct Cavern {
-    tiles: Vec<Tile>,
-    width: usize,
-    height: usize,
+    tiles: Grid<Tile>,
     fighters: Vec<Fighter>,
     fighter_positions: HashMap<Point, usize>,
 
@@ -118,36 +119,23 @@ struct Cavern {
 
 impl Cavern {
     fn parse(s: &str) -> Self {
-        let mut width = 0;
-        let mut height = 0;
         let mut fighters = Vec::new();
-        let mut tiles = Vec::new();
-
-        for (y, line) in s.lines().enumerate() {
-            height += 1;
-            width = line.len(); // assume all lines are the same length
-
-            for (x, char) in line.chars().enumerate() {
-                let point = Point::new(x as isize, y as isize);
-                match char {
-                    '#' => tiles.push(Tile::Blocked),
-                    'E' => {
-                        tiles.push(Tile::Empty);
-                        fighters.push(Fighter::new(Team::Elf, point));
-                    }
-                    'G' => {
-                        tiles.push(Tile::Empty);
-                        fighters.push(Fighter::new(Team::Goblin, point));
-                    }
-                    _ => tiles.push(Tile::Empty),
-                }
+
+        let tiles = Grid::parse(s, |point, char| match char {
+            '#' => Tile::Blocked,
+            'E' => {
+                fighters.push(Fighter::new(Team::Elf, point));
+                Tile::Empty
             }
-        }
+            'G' => {
+                fighters.push(Fighter::new(Team::Goblin, point));
+                Tile::Empty
+            }
+            _ => Tile::Empty,
+        });
 
         let mut cavern = Self {
             tiles,
-            width,
-            height,
             fighters,
             fighter_positions: HashMap::new(),
 
@@ -179,12 +167,7 @@ impl Cavern {
     }
 
     fn tile_at(&self, point: Point) -> Tile {
-        let off = self.width as isize * point.y + point.x;
-        if off >= 0 && off < self.tiles.len() as isize {
-            self.tiles[off as usize]
-        } else {
-            Tile::Blocked
-        }
+        self.tiles.get(point).cloned().unwrap_or(Tile::Blocked)
     }
 
     fn find_targets(&self, i: usize, targets: &mut Vec<usize>) {
@@ -212,6 +195,15 @@ impl Cavern {
             .collect();
 
         if !dests.contains(&fighter.pos) {
+            // the pathfinder searches every reachable tile before giving up on a destination,
+            // so rule out the ones which can't be reached at all first
+            let reachable: HashSet<_> = flood_fill(&self.tiles,
+                                                   fighter.pos,
+                                                   |p, _| self.is_free_space(p),
+                                                   Point::neighbors_reading_order)
+                .into_iter()
+                .collect();
+
             let mut paths = Vec::new();
 
             let origin_points = fighter.pos.neighbors_reading_order()
@@ -219,7 +211,7 @@ impl Cavern {
 
             let mut path = Vec::new();
             for origin in origin_points {
-                for &dest in &dests {
+                for &dest in dests.iter().filter(|dest| reachable.contains(dest)) {
                     let free_tile_pred = |p: &Point| self.is_free_space(*p);
 
                     if pathfinder.find_path(origin, dest, free_tile_pred, &mut path) {
@@ -313,26 +305,150 @@ impl Cavern {
     }
 }
 
+impl Cavern {
+    fn renderer(&self) -> Renderer {
+        Renderer::new(self.tiles.bounds().expect("cavern must not be empty"))
+    }
+
+    fn glyph_at(&self, pos: Point) -> Glyph {
+        match self.fighter_at(pos) {
+            Some(i) => match self.fighters[i].team {
+                Team::Elf => Glyph::colored('E', Color::Green),
+                Team::Goblin => Glyph::colored('G', Color::Red),
+            },
+            None => match self.tile_at(pos) {
+                Tile::Empty => Glyph::colored('.', Color::Grey),
+                Tile::Blocked => Glyph::new('#'),
+            },
+        }
+    }
+
+    fn to_image(&self) -> Image {
+        let bounds = self.tiles.bounds().expect("cavern must not be empty");
+
+        Image::render(bounds, 8, |pos| match self.fighter_at(pos) {
+            Some(i) => match self.fighters[i].team {
+                Team::Elf => Rgb::new(60, 200, 60),
+                Team::Goblin => Rgb::new(210, 50, 50),
+            },
+            None => match self.tile_at(pos) {
+                Tile::Empty => Rgb::new(200, 190, 170),
+                Tile::Blocked => Rgb::new(70, 60, 50),
+            },
+        })
+    }
+
+    /// The HP of the fighters on a row, in the same format as the puzzle's examples
+    fn row_annotation(&self, y: isize) -> Option<String> {
+        let mut row_fighters: Vec<_> = self.fighters.iter()
+            .filter(|f| f.hp > 0 && f.pos.y == y)
+            .collect();
+        row_fighters.sort_by_key(|f| f.pos.x);
+
+        let fighter_hps: Vec<_> = row_fighters.iter()
+            .map(|f| match f.team {
+                Team::Elf => format!("E({})", f.hp),
+                Team::Goblin => format!("G({})", f.hp),
+            })
+            .collect();
+
+        if fighter_hps.is_empty() {
+            None
+        } else {
+            Some(fighter_hps.join(", "))
+        }
+    }
+}
+
+/// The alternate format (`{:#}`) draws the cavern in colour
 impl fmt::Display for Cavern {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for y in 0..self.height as isize {
-            for x in 0..self.width as isize {
-                let pos = Point::new(x, y);
-                match self.fighter_at(pos) {
-                    Some(fighter_pos) => match self.fighters[fighter_pos].team {
-                        Team::Elf => write!(f, "E")?,
-                        Team::Goblin => write!(f, "G")?,
-                    }
-                    None => write!(f, "{}", self.tile_at(pos))?,
-                }
+        self.renderer()
+            .colors(f.alternate())
+            .render_annotated(f, |pos| self.glyph_at(pos), |y| self.row_annotation(y))
+    }
+}
+
+/// One battle for the stepper. Every copy gets its own pathfinder, since they only hold scratch
+/// space for the search.
+struct Battle {
+    cavern: Cavern,
+    pathfinder: CavernPathfinder,
+    winner: Option<Team>,
+}
+
+impl Battle {
+    fn new(cavern: Cavern) -> Self {
+        Self {
+            cavern,
+            pathfinder: CavernPathfinder::new(),
+            winner: None,
+        }
+    }
+
+    fn alive(&self, team: Team) -> usize {
+        self.cavern.fighters.iter().filter(|f| f.hp > 0 && f.team == team).count()
+    }
+}
+
+impl Clone for Battle {
+    fn clone(&self) -> Self {
+        Self {
+            winner: self.winner,
+            ..Battle::new(self.cavern.clone())
+        }
+    }
+}
+
+impl Simulation for Battle {
+    fn bounds(&self) -> Option<BoundingBox> {
+        self.cavern.tiles.bounds()
+    }
+
+    fn glyph_at(&self, pos: Point) -> Glyph {
+        self.cavern.glyph_at(pos)
+    }
+
+    fn annotation(&self, y: isize) -> Option<String> {
+        self.cavern.row_annotation(y)
+    }
+
+    fn advance(&mut self) -> Option<String> {
+        let (elves, goblins) = (self.alive(Team::Elf), self.alive(Team::Goblin));
+        self.winner = self.cavern.tick(&mut self.pathfinder);
+
+        let deaths = (elves - self.alive(Team::Elf), goblins - self.alive(Team::Goblin));
+
+        match (self.winner, deaths) {
+            (Some(winner), _) => Some(format!("{} team wins", winner)),
+            (None, (0, 0)) => None,
+            (None, (elf_deaths, goblin_deaths)) => Some(format!(
+                "{} elves and {} goblins died, {} elves and {} goblins left",
+                elf_deaths, goblin_deaths, self.alive(Team::Elf), self.alive(Team::Goblin)
+            )),
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.winner.is_some()
+    }
+
+    fn inspect(&self, pos: Point) -> Option<String> {
+        match self.cavern.fighter_at(pos) {
+            Some(i) => {
+                let fighter = &self.cavern.fighters[i];
+                Some(format!("{} with {} HP", fighter.team, fighter.hp))
             }
-            writeln!(f)?;
+            None => match self.cavern.tile_at(pos) {
+                Tile::Empty => None,
+                Tile::Blocked => Some("wall".to_string()),
+            },
         }
-        Ok(())
     }
 }
 
 struct Outcome {
+    cavern: Cavern,
     elf_power: isize,
     elves_remaining: Vec<Fighter>,
     winner: Team,
@@ -346,6 +462,7 @@ impl Outcome {
 
 
         Self {
+            cavern: cavern.clone(),
             hp_sum,
             elf_power: cavern.elf_attack_power,
             elves_remaining: cavern.elves().cloned().collect(),
@@ -375,6 +492,11 @@ fn main() {
     let input = include_str!("day_15.txt");
     let initial_state = Cavern::parse(input);
 
+    if stepper::requested() {
+        Stepper::new(Battle::new(initial_state)).run_terminal().expect("failed to run stepper");
+        return;
+    }
+
     let total_start_time = Instant::now();
 
     let initial_elves = initial_state.elves().count();
@@ -385,7 +507,7 @@ fn main() {
         let chunk_outcomes: Vec<Outcome> = (0..chunk_size).into_par_iter()
             .map(|i| {
                 let mut pathfinder = CavernPathfinder::new();
-                let attack_boost = (chunk_size * chunk + i) as isize;
+                let attack_boost = chunk_size * chunk + i;
 
                 let mut cavern = initial_state.clone();
                 cavern.elf_attack_power += attack_boost;
@@ -414,6 +536,30 @@ fn main() {
         println!("  surviving elf with {} HP", elf.hp);
     }
 
+    if let Some(path) = image::export_path() {
+        winning_outcome.cavern.to_image().save_ppm(&path).expect("failed to save image");
+        println!("saved final state to {}", path.display());
+    }
+
+    // the search runs battles in parallel, so replay the winning one to record it
+    if let Some(mut recorder) = Recorder::from_args() {
+        let mut pathfinder = CavernPathfinder::new();
+        let mut cavern = initial_state.clone();
+        cavern.elf_attack_power = winning_outcome.elf_power;
+
+        recorder.record(0, || cavern.to_string()).expect("failed to record frame");
+        for time in 1.. {
+            let finished = cavern.tick(&mut pathfinder).is_some();
+            recorder.record(time, || cavern.to_string()).expect("failed to record frame");
+
+            if finished || recorder.is_done(time) {
+                break;
+            }
+        }
+
+        println!("recorded {} frames to {}", recorder.frames(), recorder.dir().display());
+    }
+
     let total_elapsed = Instant::now() - total_start_time;
     println!("elapsed time: {}.{}s", total_elapsed.as_secs(), total_elapsed.subsec_millis());
 }
@@ -1,54 +1,112 @@
+mod near_duplicates;
+mod args;
+
 use {
-    levenshtein::levenshtein,
+    crate::near_duplicates::{near_duplicates, Distance},
+    std::collections::{BTreeMap, HashMap},
 };
 
+/// How many times each letter appears in an ID
+fn letter_counts(id: &str) -> HashMap<char, usize> {
+    let mut counts = HashMap::new();
+    for c in id.chars() {
+        *counts.entry(c).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Letter counts for every ID in a list, and how they repeat across the list
+struct LetterReport {
+    /// How many times each letter appears in each ID, in the same order as the IDs
+    per_id: Vec<HashMap<char, usize>>,
+    /// For each n, how many IDs have at least one letter which appears exactly n times
+    histogram: BTreeMap<usize, usize>,
+}
+
+impl LetterReport {
+    fn new(ids: &[&str]) -> Self {
+        let per_id: Vec<_> = ids.iter().map(|id| letter_counts(id)).collect();
+
+        let mut histogram = BTreeMap::new();
+        for counts in &per_id {
+            let mut distinct_counts: Vec<_> = counts.values().cloned().collect();
+            distinct_counts.sort();
+            distinct_counts.dedup();
+
+            for n in distinct_counts {
+                *histogram.entry(n).or_insert(0) += 1;
+            }
+        }
+
+        Self { per_id, histogram }
+    }
+
+    /// The product of the number of IDs with a letter appearing exactly n times, for each n
+    fn checksum(&self, repeats: &[usize]) -> usize {
+        repeats.iter()
+            .map(|n| self.histogram.get(n).cloned().unwrap_or(0))
+            .product()
+    }
+}
+
 fn main() {
     let input = include_str!("day_2.txt");
-    let ids: Vec<String> = input.lines()
-        .map(|line| line.chars().collect())
-        .collect();
-
-    let count_ids_with_n_repeated_chars = |n| {
-        ids.iter()
-            .filter(|line| {
-                line.chars()
-                    .map(|a| line.chars().filter(|b| a == *b).count())
-                    .any(|count| count == n)
-            })
-            .count()
-    };
-
-    let ids_2_repeated = count_ids_with_n_repeated_chars(2);
-    let ids_3_repeated = count_ids_with_n_repeated_chars(3);
-
-    let checksum = ids_2_repeated * ids_3_repeated;
-
-    println!("checksum: {}", checksum);
-
-    let mut similar_ids = None;
-    for id_a in ids.iter() {
-        for id_b in ids.iter().skip(1) {
-            if levenshtein(id_a, id_b) == 1 {
-                similar_ids = Some((id_a, id_b));
-                break;
-            }
+    let ids: Vec<&str> = input.lines().collect();
+
+    let report = LetterReport::new(&ids);
+
+    println!("checksum: {}", report.checksum(&[2, 3]));
+
+    println!("IDs with a letter appearing exactly n times:");
+    for (n, ids) in &report.histogram {
+        println!("  {}: {}", n, ids);
+    }
+
+    if args::flag("--letters") {
+        println!("letter counts per ID:");
+        for (id, counts) in ids.iter().zip(&report.per_id) {
+            let mut counts: Vec<_> = counts.iter().collect();
+            counts.sort();
+
+            let counts: Vec<_> = counts.iter().map(|(c, n)| format!("{}={}", c, n)).collect();
+            println!("  {}: {}", id, counts.join(" "));
         }
+    }
 
-        if let Some((id_a, id_b)) = similar_ids {
+    match near_duplicates(&ids, Distance::Hamming, 1).first() {
+        Some(pair) => {
             println!("similar IDs:");
-            println!("  {}", id_a);
-            println!("  {}", id_b);
-
-            let mut common_letters = String::new();
-            for i in 0..id_a.len() {
-                let c = id_a.chars().skip(i).next().unwrap();
-                if c == id_b.chars().skip(i).next().unwrap() {
-                    common_letters.push(c);
-                }
-            }
-
-            println!("common letters: {}", common_letters);
-            break;
+            println!("  {}", ids[pair.a]);
+            println!("  {}", ids[pair.b]);
+            println!("common letters: {}", pair.common);
         }
+        None => println!("no similar IDs"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn puzzle_example() {
+        let ids = ["abcdef", "bababc", "abbcde", "abcccd", "aabcdd", "abcdee", "ababab"];
+        let report = LetterReport::new(&ids);
+
+        assert_eq!(12, report.checksum(&[2, 3]));
+        assert_eq!(Some(&4), report.histogram.get(&2));
+        assert_eq!(Some(&3), report.histogram.get(&3));
+
+        let bababc: HashMap<_, _> = vec![('a', 2), ('b', 3), ('c', 1)].into_iter().collect();
+        assert_eq!(bababc, report.per_id[1]);
+    }
+
+    #[test]
+    fn part_2_example() {
+        let ids = ["abcde", "fghij", "klmno", "pqrst", "fguij", "axcye", "wvxyz"];
+        let pairs = near_duplicates(&ids, Distance::Hamming, 1);
+
+        assert_eq!(1, pairs.len());
+        assert_eq!("fgij", pairs[0].common);
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,21 @@
+mod point;
+mod summed_area;
+mod coverage;
+mod conflicts;
+mod args;
+
 use {
+    crate::{
+        point::{BoundingBox, Point},
+        coverage::Coverage,
+        conflicts::ConflictGraph,
+    },
+    std::{
+        fs::File,
+        io::BufWriter,
+        path::PathBuf,
+    },
     regex::Regex,
-    std::collections::hash_map::{Entry, HashMap},
 };
 
 struct Claim {
@@ -30,33 +45,41 @@ impl Claim {
         }
     }
 
-    fn right(&self) -> usize {
-        self.x + self.width
-    }
+    /// The square inches covered by the claim, or `None` if it has no area
+    fn bounds(&self) -> Option<BoundingBox> {
+        if self.width == 0 || self.height == 0 {
+            return None;
+        }
+
+        let top_left = Point::new(self.x as isize, self.y as isize);
+        let size = Point::new(self.width as isize, self.height as isize);
 
-    fn bottom(&self) -> usize {
-        self.y + self.height
+        Some(BoundingBox::new(top_left, top_left + size - Point::new(1, 1)))
     }
+}
 
-    fn claim_points<'a>(&'a self, cloth: &mut HashMap<(usize, usize), Vec<&'a Self>>) {
-        let xs = self.x..self.right();
-
-        let coords = xs.flat_map(|x| {
-            let ys = self.y..self.bottom();
-            ys.map(move |y| (x, y))
-        });
-
-        for (x, y) in coords {
-            match cloth.entry((x, y)) {
-                Entry::Occupied(mut entry) => {
-                    entry.get_mut().push(self);
-                }
-                Entry::Vacant(entry) => {
-                    entry.insert(vec![self]);
-                }
-            }
-        }
+/// The index of each claim which covers some fabric, and the bounds of each of them in the same
+/// order
+fn covering_claims(claims: &[Claim]) -> (Vec<usize>, Vec<BoundingBox>) {
+    claims.iter()
+        .enumerate()
+        .filter_map(|(i, claim)| claim.bounds().map(|bounds| (i, bounds)))
+        .unzip()
+}
+
+/// The IDs of the claims which overlap no other claim, in the order they were made. A claim
+/// which covers no fabric can't overlap anything, so it's always one of them.
+fn uncontested_claims(claims: &[Claim], covering: &[usize], coverage: &Coverage) -> Vec<usize> {
+    let mut isolated: Vec<_> = claims.iter().map(|claim| claim.bounds().is_none()).collect();
+    for i in coverage.isolated() {
+        isolated[covering[i]] = true;
     }
+
+    claims.iter()
+        .zip(isolated)
+        .filter(|(_, isolated)| *isolated)
+        .map(|(claim, _)| claim.id)
+        .collect()
 }
 
 fn main() {
@@ -76,29 +99,77 @@ fn main() {
         .map(|line| Claim::read_line(line, &claim_pattern))
         .collect();
 
-    let mut cloth = HashMap::new();
-    for claim in claims.iter() {
-        claim.claim_points(&mut cloth);
+    // claims which cover no fabric are left out of the coverage and conflicts, which are
+    // indexed by their position in `covering`
+    let (covering, claim_bounds) = covering_claims(&claims);
+    if covering.len() < claims.len() {
+        println!("{} claims cover no fabric", claims.len() - covering.len());
     }
 
-    let dup_claims = cloth.values().filter(|claims| claims.len() > 1).count();
-
-    println!("number of duplicate claimed coordinates: {}", dup_claims);
-
-    let winning_claim_id = claims
-        .iter()
-        .filter_map(|claim| {
-            for x in claim.x..claim.right() {
-                for y in claim.y..claim.bottom() {
-                    if cloth[&(x, y)].len() > 1 {
-                        return None;
-                    }
-                }
-            }
-            Some(claim.id)
-        })
-        .next()
-        .unwrap();
+    let coverage = Coverage::new(&claim_bounds);
+
+    println!("number of duplicate claimed coordinates: {}", coverage.overlap_area());
+
+    let uncontested = uncontested_claims(&claims, &covering, &coverage);
 
+    let winning_claim_id = uncontested.first().expect("a claim must overlap no other claims");
     println!("winning claim: {}", winning_claim_id);
+
+    if uncontested.len() > 1 {
+        println!("other claims which overlap nothing: {:?}", &uncontested[1..]);
+    }
+
+    let most_contested = (0..covering.len()).max_by_key(|i| coverage.rect_overlap_area(*i));
+    if let Some(i) = most_contested {
+        println!("most contested claim: {} ({} square inches shared)",
+                 claims[covering[i]].id, coverage.rect_overlap_area(i));
+    }
+
+    let graph = ConflictGraph::new(&claim_bounds);
+    let largest_group = graph.groups.iter().map(Vec::len).max().unwrap_or(0);
+
+    println!("{} pairs of claims overlap, in {} groups (largest: {} claims)",
+             graph.conflicts.len(), graph.groups.len(), largest_group);
+
+    // the path given with `--conflicts` ends in `.csv` or `.dot` to pick the format
+    if let Some(path) = args::value("--conflicts").map(PathBuf::from) {
+        let mut out = BufWriter::new(File::create(&path).expect("failed to create export file"));
+        let label = |i: usize| format!("#{}", claims[covering[i]].id);
+
+        let result = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("dot") => graph.write_dot(&mut out, label),
+            _ => graph.write_csv(&mut out, label),
+        };
+        result.expect("failed to export conflicts");
+
+        println!("saved conflicts to {}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn claim(id: usize, x: usize, y: usize, width: usize, height: usize) -> Claim {
+        Claim { id, x, y, width, height }
+    }
+
+    #[test]
+    fn claims_with_no_area_are_uncontested() {
+        // the puzzle example, with claims of no width and no height inside the overlap
+        let claims = vec![
+            claim(1, 1, 3, 4, 4),
+            claim(2, 3, 1, 4, 4),
+            claim(3, 5, 5, 2, 2),
+            claim(4, 3, 3, 0, 2),
+            claim(5, 4, 4, 2, 0),
+        ];
+
+        let (covering, claim_bounds) = covering_claims(&claims);
+        assert_eq!(vec![0, 1, 2], covering);
+
+        let coverage = Coverage::new(&claim_bounds);
+        assert_eq!(4, coverage.overlap_area());
+        assert_eq!(vec![3, 4, 5], uncontested_claims(&claims, &covering, &coverage));
+    }
 }
@@ -1,12 +1,17 @@
+mod args;
+
 use {
     std::{
         fmt,
-        collections::HashMap,
+        collections::BTreeMap,
+        fs::File,
+        io::{self, BufWriter, Write},
+        ops::Range,
     },
-    chrono::{DateTime, TimeZone, Timelike, Utc}
+    chrono::{DateTime, Duration, NaiveDate, TimeZone, Timelike, Utc}
 };
 
-#[derive(Debug)]
+#[derive(Debug, Eq, PartialEq)]
 enum SleepLogEvent {
     NewGuard(usize),
     FallsAsleep,
@@ -15,14 +20,32 @@ enum SleepLogEvent {
 
 #[derive(Debug)]
 struct SleepLog {
+    line: usize,
     timestamp: DateTime<Utc>,
     event: SleepLogEvent,
 }
 
 const DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
 
+const MINUTES_PER_DAY: usize = 24 * 60;
+
+fn minute_of_day(time: DateTime<Utc>) -> usize {
+    time.hour() as usize * 60 + time.minute() as usize
+}
+
+/// Shifts are for the midnight hour, so their minutes are counted from the noon before. That
+/// keeps a nap across midnight in one piece.
+fn minute_of_shift(time: DateTime<Utc>) -> usize {
+    (minute_of_day(time) + MINUTES_PER_DAY / 2) % MINUTES_PER_DAY
+}
+
+/// The minute of the day for a minute counted by `minute_of_shift`
+fn clock_minute(minute_of_shift: usize) -> usize {
+    (minute_of_shift + MINUTES_PER_DAY / 2) % MINUTES_PER_DAY
+}
+
 impl SleepLog {
-    fn parse(s: &str) -> Self {
+    fn parse(line: usize, s: &str) -> Self {
         let timestamp = Utc.datetime_from_str(&s[1..17], DATE_FORMAT).unwrap();
 
         let event_desc = &s[19..];
@@ -41,10 +64,31 @@ impl SleepLog {
             }
         };
 
-        Self { timestamp, event }
+        Self { line, timestamp, event }
+    }
+
+    /// Orders entries by time. Entries at the same time are ordered so that a guard wakes up
+    /// before the next shift begins, and a shift begins before its guard falls asleep.
+    fn sort_key(&self) -> (DateTime<Utc>, usize, usize) {
+        let (rank, id) = match self.event {
+            SleepLogEvent::WakesUp => (0, 0),
+            SleepLogEvent::NewGuard(id) => (1, id),
+            SleepLogEvent::FallsAsleep => (2, 0),
+        };
+        (self.timestamp, rank, id)
     }
 }
 
+/// Parses every line of the log, sorted by `SleepLog::sort_key`
+fn parse_log(input: &str) -> Vec<SleepLog> {
+    let mut log: Vec<_> = input.lines()
+        .enumerate()
+        .map(|(i, line)| SleepLog::parse(i + 1, line))
+        .collect();
+    log.sort_by_key(SleepLog::sort_key);
+    log
+}
+
 impl fmt::Display for SleepLog {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let timestamp = self.timestamp.format(DATE_FORMAT);
@@ -58,96 +102,570 @@ impl fmt::Display for SleepLog {
     }
 }
 
+struct Nap {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+impl Nap {
+    /// The start of each minute the guard was asleep
+    fn minutes(&self) -> impl Iterator<Item=DateTime<Utc>> + '_ {
+        (0..)
+            .map(move |minute| self.start + Duration::minutes(minute))
+            .take_while(move |time| *time < self.end)
+    }
+}
+
 struct Shift {
     guard_id: usize,
+    start: DateTime<Utc>,
+    naps: Vec<Nap>,
+}
+
+impl Shift {
+    /// The day the shift is for. Shifts are for the midnight hour, so a guard who starts in the
+    /// evening is on duty for the next day.
+    fn date(&self) -> NaiveDate {
+        let start = self.start.naive_utc();
+        if start.hour() >= 12 {
+            start.date().succ()
+        } else {
+            start.date()
+        }
+    }
+
+    /// Whether the guard was asleep at each minute of the shift, counted by `minute_of_shift`
+    fn timeline(&self) -> Vec<bool> {
+        let mut timeline = vec![false; MINUTES_PER_DAY];
+        for minute in self.naps.iter().flat_map(Nap::minutes) {
+            timeline[minute_of_shift(minute)] = true;
+        }
+        timeline
+    }
+}
 
-    last_asleep: Option<usize>, // in minutes past midnight
-    minutes_asleep: [bool; 60], // which minutes past midnight was this guard asleep
+#[derive(Debug, Eq, PartialEq)]
+enum AnomalyKind {
+    /// The same event at the same time as the entry before it
+    Duplicate,
+    /// An entry before any guard's shift has begun
+    NoGuardOnDuty,
+    AlreadyAsleep,
+    NotAsleep,
+    /// A guard fell asleep and the next entry is a new shift, so the nap ends when that shift
+    /// begins
+    NoWakeUpBeforeNextShift,
+    /// A guard fell asleep and the log ends, so there's no way to tell how long they slept
+    NoWakeUpBeforeEndOfLog,
 }
 
-fn main() {
-    let input = include_str!("day_4.txt");
+/// A log entry which doesn't fit with the rest of the log
+struct Anomaly<'a> {
+    entry: &'a SleepLog,
+    kind: AnomalyKind,
+}
+
+impl fmt::Display for Anomaly<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let problem = match self.kind {
+            AnomalyKind::Duplicate => "duplicate of the previous entry, ignored",
+            AnomalyKind::NoGuardOnDuty => "no guard is on duty yet, ignored",
+            AnomalyKind::AlreadyAsleep => "guard is already asleep, ignored",
+            AnomalyKind::NotAsleep => "guard is not asleep, ignored",
+            AnomalyKind::NoWakeUpBeforeNextShift => "never woke up, nap ends at the next shift",
+            AnomalyKind::NoWakeUpBeforeEndOfLog => "never woke up before the log ends, nap ignored",
+        };
+
+        write!(f, "line {}: {}: {}", self.entry.line, self.entry, problem)
+    }
+}
+
+struct Reconstruction<'a> {
+    shifts: Vec<Shift>,
+    anomalies: Vec<Anomaly<'a>>,
+}
+
+/// Works out each shift and its naps from a log sorted by `SleepLog::sort_key`. Entries which
+/// don't make sense are skipped or worked around where possible, and reported as anomalies
+/// instead of stopping the whole reconstruction.
+fn reconstruct_shifts(log: &[SleepLog]) -> Reconstruction<'_> {
+    let mut shifts = Vec::new();
+    let mut anomalies = Vec::new();
 
-    let mut log: Vec<_> = input.lines().map(SleepLog::parse).collect();
-    log.sort_by_key(|entry| entry.timestamp);
+    let mut current: Option<Shift> = None;
+    let mut asleep_since: Option<&SleepLog> = None;
+    let mut previous: Option<&SleepLog> = None;
 
-    let shifts = log.iter().fold(Vec::new(), |mut shifts, entry| {
-        match &entry.event {
-            SleepLogEvent::NewGuard(id) => {
-                shifts.push(Shift {
+    for entry in log {
+        let duplicate = previous.map_or(false, |previous| {
+            previous.timestamp == entry.timestamp && previous.event == entry.event
+        });
+        previous = Some(entry);
+
+        if duplicate {
+            anomalies.push(Anomaly { entry, kind: AnomalyKind::Duplicate });
+            continue;
+        }
+
+        match (&entry.event, current.as_mut()) {
+            (SleepLogEvent::NewGuard(id), _) => {
+                if let Some(fell_asleep) = asleep_since.take() {
+                    anomalies.push(Anomaly {
+                        entry: fell_asleep,
+                        kind: AnomalyKind::NoWakeUpBeforeNextShift,
+                    });
+
+                    if let Some(shift) = current.as_mut() {
+                        shift.naps.push(Nap { start: fell_asleep.timestamp, end: entry.timestamp });
+                    }
+                }
+
+                shifts.extend(current.take());
+                current = Some(Shift {
                     guard_id: *id,
-                    minutes_asleep: [false; 60],
-                    last_asleep: None,
+                    start: entry.timestamp,
+                    naps: Vec::new(),
                 });
             }
 
-            SleepLogEvent::FallsAsleep => {
-                let current_shift = shifts.last_mut().unwrap();
-
-                assert!(
-                    current_shift.last_asleep.is_none(),
-                    "guard {} should not already be asleep when falling asleep @ {}",
-                    current_shift.guard_id,
-                    entry.timestamp
-                );
-                current_shift.last_asleep = Some(entry.timestamp.minute() as usize);
+            (_, None) => {
+                anomalies.push(Anomaly { entry, kind: AnomalyKind::NoGuardOnDuty });
             }
 
-            SleepLogEvent::WakesUp => {
-                let current_shift = shifts.last_mut().unwrap();
-
-                let last_asleep = current_shift.last_asleep.unwrap();
-                let entry_time = entry.timestamp.minute() as usize;
-                for minute in last_asleep..entry_time {
-                    current_shift.minutes_asleep[minute] = true;
+            (SleepLogEvent::FallsAsleep, Some(_)) => {
+                if asleep_since.is_some() {
+                    anomalies.push(Anomaly { entry, kind: AnomalyKind::AlreadyAsleep });
+                } else {
+                    asleep_since = Some(entry);
                 }
+            }
 
-                current_shift.last_asleep = None;
+            (SleepLogEvent::WakesUp, Some(shift)) => {
+                match asleep_since.take() {
+                    Some(fell_asleep) => {
+                        shift.naps.push(Nap { start: fell_asleep.timestamp, end: entry.timestamp });
+                    }
+                    None => anomalies.push(Anomaly { entry, kind: AnomalyKind::NotAsleep }),
+                }
             }
-        };
+        }
+    }
+
+    if let Some(fell_asleep) = asleep_since {
+        anomalies.push(Anomaly { entry: fell_asleep, kind: AnomalyKind::NoWakeUpBeforeEndOfLog });
+    }
+    shifts.extend(current);
+
+    anomalies.sort_by_key(|anomaly| anomaly.entry.line);
+
+    Reconstruction { shifts, anomalies }
+}
+
+/// Everything one guard did across all their shifts
+struct GuardProfile {
+    guard_id: usize,
+    total_minutes: usize,
+    /// How many times the guard was asleep at each minute of the shift, counted by
+    /// `minute_of_shift`
+    minutes: Vec<usize>,
+    /// Which minutes the guard was asleep for each shift, in date order
+    timelines: Vec<(NaiveDate, Vec<bool>)>,
+}
 
-        shifts
-    });
+impl GuardProfile {
+    /// The profile of every guard in the shifts, ordered by ID
+    fn build(shifts: &[Shift]) -> Vec<GuardProfile> {
+        let mut profiles: BTreeMap<usize, GuardProfile> = BTreeMap::new();
 
-    let mut guard_profiles = HashMap::new();
-    for shift in shifts {
-        let profile = guard_profiles.entry(shift.guard_id)
-            .or_insert_with(|| [0usize; 60]);
+        for shift in shifts {
+            let profile = profiles.entry(shift.guard_id)
+                .or_insert_with(|| GuardProfile {
+                    guard_id: shift.guard_id,
+                    total_minutes: 0,
+                    minutes: vec![0; MINUTES_PER_DAY],
+                    timelines: Vec::new(),
+                });
 
-        for (minute, asleep) in shift.minutes_asleep.iter().enumerate() {
-            if *asleep {
-                profile[minute] += 1;
+            let timeline = shift.timeline();
+            for (minute, asleep) in timeline.iter().enumerate() {
+                if *asleep {
+                    profile.minutes[minute] += 1;
+                    profile.total_minutes += 1;
+                }
             }
+            profile.timelines.push((shift.date(), timeline));
+        }
+
+        let mut profiles: Vec<_> = profiles.into_values().collect();
+        for profile in &mut profiles {
+            profile.timelines.sort_by_key(|(date, _)| *date);
         }
+        profiles
     }
 
-    let sleepiest_guard = guard_profiles.iter()
-        .map(|(id, profile)| (id, profile.iter().cloned().sum::<usize>()))
-        .max_by_key(|(_id, minutes)| *minutes)
-        .map(|(id, _minutes)| id)
-        .unwrap();
-    let sleepiest_minute = guard_profiles.get(sleepiest_guard).unwrap().iter()
-        .enumerate()
-        .max_by_key(|(_minute, times_asleep)| **times_asleep)
-        .map(|(minute, _)| minute)
-        .unwrap();
+    /// The minute of the day this guard was most often asleep and how many times, or `None`
+    /// if they never slept
+    fn sleepiest_minute(&self) -> Option<(usize, usize)> {
+        self.minutes.iter().cloned()
+            .enumerate()
+            .filter(|(_, times)| *times > 0)
+            .max_by_key(|(_, times)| *times)
+            .map(|(minute, times)| (clock_minute(minute), times))
+    }
+}
+
+/// Chooses the guard with the highest `score` who slept at all, and their sleepiest minute
+fn apply_strategy<K: Ord>(profiles: &[GuardProfile],
+                          score: impl Fn(&GuardProfile) -> K) -> Option<(usize, usize)> {
+    profiles.iter()
+        .filter_map(|profile| profile.sleepiest_minute().map(|(minute, _)| (profile, minute)))
+        .max_by_key(|(profile, _)| score(profile))
+        .map(|(profile, minute)| (profile.guard_id, minute))
+}
+
+/// The whole hours during which any guard was ever asleep, as minutes counted by
+/// `minute_of_shift`
+fn active_hours(profiles: &[GuardProfile]) -> Option<Range<usize>> {
+    let asleep = |minute: &usize| profiles.iter().any(|profile| profile.minutes[*minute] > 0);
+
+    let first = (0..MINUTES_PER_DAY).find(asleep)?;
+    let last = (0..MINUTES_PER_DAY).rev().find(asleep)?;
+    Some(first / 60 * 60..(last / 60 + 1) * 60)
+}
+
+fn format_minute(minute: usize) -> String {
+    let minute = clock_minute(minute);
+    format!("{:02}:{:02}", minute / 60, minute % 60)
+}
+
+/// How many times each guard was asleep at each minute in `minutes`, one row per guard
+fn write_minute_matrix(out: &mut impl Write,
+                       profiles: &[GuardProfile],
+                       minutes: Range<usize>) -> io::Result<()> {
+    let header: Vec<_> = minutes.clone().map(format_minute).collect();
+    writeln!(out, "guard,{}", header.join(","))?;
+
+    for profile in profiles {
+        let counts: Vec<_> = profile.minutes[minutes.clone()].iter()
+            .map(usize::to_string)
+            .collect();
+        writeln!(out, "{},{}", profile.guard_id, counts.join(","))?;
+    }
+
+    Ok(())
+}
+
+/// Each shift on its own row with `#` for the minutes the guard was asleep, like the chart in
+/// the puzzle
+fn write_timeline_chart(out: &mut impl Write,
+                        profiles: &[GuardProfile],
+                        minutes: Range<usize>) -> io::Result<()> {
+    let mut rows: Vec<_> = profiles.iter()
+        .flat_map(|profile| {
+            profile.timelines.iter()
+                .map(move |(date, timeline)| (*date, profile.guard_id, timeline))
+        })
+        .collect();
+    rows.sort_by_key(|(date, guard_id, _)| (*date, *guard_id));
+
+    let id_width = rows.iter().map(|(_, id, _)| id.to_string().len() + 1).max().unwrap_or(0);
+    let margin = format!("{:1$}", "", "MM-DD  ".len() + id_width + 2);
+
+    let digits = |digit: fn(usize) -> usize| -> String {
+        minutes.clone().map(|minute| (b'0' + digit(clock_minute(minute)) as u8) as char).collect()
+    };
+
+    writeln!(out, "{:<1$}Minute", format!("Date   {:1$}", "ID", id_width), margin.len())?;
+    if minutes.start / 60 != (minutes.end - 1) / 60 {
+        writeln!(out, "{}{}", margin, digits(|minute| minute / 60 % 10))?;
+    }
+    writeln!(out, "{}{}", margin, digits(|minute| minute % 60 / 10))?;
+    writeln!(out, "{}{}", margin, digits(|minute| minute % 10))?;
+
+    for (date, guard_id, timeline) in rows {
+        let chart: String = timeline[minutes.clone()].iter()
+            .map(|asleep| if *asleep { '#' } else { '.' })
+            .collect();
+
+        let guard = format!("#{}", guard_id);
+        writeln!(out, "{}  {:<3$}  {}", date.format("%m-%d"), guard, chart, id_width)?;
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let input = include_str!("day_4.txt");
+
+    let log = parse_log(input);
+    let Reconstruction { shifts, anomalies } = reconstruct_shifts(&log);
+
+    if !anomalies.is_empty() {
+        println!("{} log anomalies:", anomalies.len());
+        for anomaly in &anomalies {
+            println!("  {}", anomaly);
+        }
+    }
+
+    let profiles = GuardProfile::build(&shifts);
+
+    let (sleepiest_guard, sleepiest_minute) = apply_strategy(&profiles, |p| p.total_minutes)
+        .expect("at least one guard must fall asleep");
 
     println!("sleepiest guard: {}, at minute {} (value: {})", sleepiest_guard,
         sleepiest_minute, sleepiest_guard * sleepiest_minute);
 
-    let (most_freq_guard_id, most_freq_minute) = guard_profiles.iter()
-        .map(|(guard_id, nap_minutes)| {
-            let (sleepiest_minute, times_asleep) = nap_minutes.iter()
-                .enumerate()
-                .max_by_key(|(_minute, times)| **times)
-                .unwrap();
-
-            (guard_id, sleepiest_minute, times_asleep)
+    let (most_freq_guard_id, most_freq_minute) = apply_strategy(&profiles, |profile| {
+            profile.sleepiest_minute().map(|(_, times)| times)
         })
-        .max_by_key(|(_guard_id, _sleepiest_minute, times_asleep)| **times_asleep)
-        .map(|(guard_id, sleepiest_minute, _)| (guard_id, sleepiest_minute))
-        .unwrap();
+        .expect("at least one guard must fall asleep");
 
     println!("most frequently asleep guard at minute {}: {} (value: {})", most_freq_minute,
         most_freq_guard_id, most_freq_minute * most_freq_guard_id);
+
+    let minutes = active_hours(&profiles).expect("at least one guard must fall asleep");
+
+    if args::flag("--timeline") {
+        let stdout = io::stdout();
+        write_timeline_chart(&mut stdout.lock(), &profiles, minutes.clone())
+            .expect("failed to write timeline");
+    }
+
+    if let Some(path) = args::value("--csv") {
+        let mut out = BufWriter::new(File::create(&path).expect("failed to create CSV file"));
+        write_minute_matrix(&mut out, &profiles, minutes).expect("failed to write CSV");
+        println!("saved minute matrix to {}", path);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:55] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:50] wakes up
+[1518-11-03 00:05] Guard #10 begins shift
+[1518-11-03 00:24] falls asleep
+[1518-11-03 00:29] wakes up
+[1518-11-04 00:02] Guard #99 begins shift
+[1518-11-04 00:36] falls asleep
+[1518-11-04 00:46] wakes up
+[1518-11-05 00:03] Guard #99 begins shift
+[1518-11-05 00:45] falls asleep
+[1518-11-05 00:55] wakes up";
+
+    fn example_profiles() -> Vec<GuardProfile> {
+        let log = parse_log(EXAMPLE);
+        GuardProfile::build(&reconstruct_shifts(&log).shifts)
+    }
+
+    fn reconstruct(lines: &[&str]) -> (Vec<Shift>, Vec<(usize, AnomalyKind)>) {
+        let log = parse_log(&lines.join("\n"));
+        let Reconstruction { shifts, anomalies } = reconstruct_shifts(&log);
+
+        let anomalies = anomalies.into_iter()
+            .map(|anomaly| (anomaly.entry.line, anomaly.kind))
+            .collect();
+        (shifts, anomalies)
+    }
+
+    /// Each nap as the minutes of the day it starts and ends
+    fn naps(shift: &Shift) -> Vec<(usize, usize)> {
+        shift.naps.iter()
+            .map(|nap| (minute_of_day(nap.start), minute_of_day(nap.end)))
+            .collect()
+    }
+
+    #[test]
+    fn shift_starting_before_midnight() {
+        let (shifts, anomalies) = reconstruct(&[
+            "[1518-11-01 23:58] Guard #99 begins shift",
+            "[1518-11-02 00:40] falls asleep",
+            "[1518-11-02 00:50] wakes up",
+        ]);
+
+        assert!(anomalies.is_empty());
+        assert_eq!(1, shifts.len());
+        assert_eq!(NaiveDate::from_ymd(1518, 11, 2), shifts[0].date());
+        assert_eq!(vec![(40, 50)], naps(&shifts[0]));
+    }
+
+    #[test]
+    fn missing_wake_up_before_new_guard() {
+        let (shifts, anomalies) = reconstruct(&[
+            "[1518-11-01 00:00] Guard #10 begins shift",
+            "[1518-11-01 00:30] falls asleep",
+            "[1518-11-02 00:00] Guard #99 begins shift",
+        ]);
+
+        assert_eq!(vec![(2, AnomalyKind::NoWakeUpBeforeNextShift)], anomalies);
+
+        // the nap lasts until the next guard arrives
+        assert_eq!(vec![(30, 0)], naps(&shifts[0]));
+        assert_eq!(MINUTES_PER_DAY - 30, shifts[0].naps[0].minutes().count());
+        assert!(shifts[1].naps.is_empty());
+    }
+
+    #[test]
+    fn missing_wake_up_after_the_midnight_hour() {
+        let (shifts, anomalies) = reconstruct(&[
+            "[1518-11-01 00:00] Guard #10 begins shift",
+            "[1518-11-01 01:30] falls asleep",
+            "[1518-11-01 03:00] Guard #99 begins shift",
+        ]);
+
+        assert_eq!(vec![(2, AnomalyKind::NoWakeUpBeforeNextShift)], anomalies);
+        assert_eq!(vec![(90, 180)], naps(&shifts[0]));
+
+        let profiles = GuardProfile::build(&shifts);
+        assert_eq!(90, profiles[0].total_minutes);
+    }
+
+    #[test]
+    fn nap_across_midnight() {
+        let (shifts, anomalies) = reconstruct(&[
+            "[1518-11-01 23:45] Guard #10 begins shift",
+            "[1518-11-01 23:50] falls asleep",
+            "[1518-11-02 00:10] wakes up",
+        ]);
+
+        assert!(anomalies.is_empty());
+        assert_eq!(vec![(23 * 60 + 50, 10)], naps(&shifts[0]));
+
+        let profiles = GuardProfile::build(&shifts);
+        assert_eq!(20, profiles[0].total_minutes);
+
+        // the hours either side of midnight, not the whole day
+        let minutes = active_hours(&profiles).unwrap();
+        assert_eq!(vec!["23:00", "00:59"], vec![
+            format_minute(minutes.start),
+            format_minute(minutes.end - 1),
+        ]);
+
+        let mut chart = Vec::new();
+        write_timeline_chart(&mut chart, &profiles, minutes).unwrap();
+        let chart = String::from_utf8(chart).unwrap();
+        let row = chart.lines().last().unwrap().split_whitespace().last().unwrap();
+        assert_eq!(format!("{}{}{}", ".".repeat(50), "#".repeat(20), ".".repeat(50)), row);
+    }
+
+    #[test]
+    fn duplicate_lines() {
+        let (shifts, anomalies) = reconstruct(&[
+            "[1518-11-01 00:05] falls asleep",
+            "[1518-11-01 00:00] Guard #10 begins shift",
+            "[1518-11-01 00:05] falls asleep",
+            "[1518-11-01 00:25] wakes up",
+            "[1518-11-01 00:25] wakes up",
+        ]);
+
+        assert_eq!(vec![(3, AnomalyKind::Duplicate), (5, AnomalyKind::Duplicate)], anomalies);
+        assert_eq!(vec![(5, 25)], naps(&shifts[0]));
+    }
+
+    #[test]
+    fn entries_at_the_same_time_dont_depend_on_input_order() {
+        let lines = [
+            "[1518-11-01 00:00] Guard #10 begins shift",
+            "[1518-11-01 00:10] falls asleep",
+            "[1518-11-01 00:20] falls asleep",
+            "[1518-11-01 00:20] wakes up",
+            "[1518-11-01 00:30] wakes up",
+            "[1518-11-01 00:30] Guard #99 begins shift",
+        ];
+
+        let mut reversed = lines;
+        reversed.reverse();
+
+        for lines in &[lines, reversed] {
+            let (shifts, anomalies) = reconstruct(lines);
+
+            assert!(anomalies.is_empty());
+            assert_eq!(vec![10, 99], shifts.iter().map(|shift| shift.guard_id).collect::<Vec<_>>());
+            assert_eq!(vec![(10, 20), (20, 30)], naps(&shifts[0]));
+        }
+    }
+
+    #[test]
+    fn falls_asleep_with_no_guard_on_duty() {
+        let (shifts, anomalies) = reconstruct(&[
+            "[1518-11-01 00:05] falls asleep",
+            "[1518-11-01 00:10] wakes up",
+            "[1518-11-01 00:20] Guard #10 begins shift",
+        ]);
+
+        let no_guard = vec![(1, AnomalyKind::NoGuardOnDuty), (2, AnomalyKind::NoGuardOnDuty)];
+        assert_eq!(no_guard, anomalies);
+        assert_eq!(1, shifts.len());
+        assert!(shifts[0].naps.is_empty());
+    }
+
+    #[test]
+    fn example_profiles_and_strategies() {
+        let profiles = example_profiles();
+
+        let totals: Vec<_> = profiles.iter().map(|p| (p.guard_id, p.total_minutes)).collect();
+        assert_eq!(vec![(10, 50), (99, 30)], totals);
+
+        assert_eq!(Some((10, 24)), apply_strategy(&profiles, |p| p.total_minutes));
+        assert_eq!(Some((99, 45)), apply_strategy(&profiles, |p| {
+            p.sleepiest_minute().map(|(_, times)| times)
+        }));
+    }
+
+    #[test]
+    fn shift_dates() {
+        let log = parse_log(EXAMPLE);
+        let dates: Vec<_> = reconstruct_shifts(&log).shifts.iter()
+            .map(|shift| shift.date().format("%m-%d").to_string())
+            .collect();
+
+        assert_eq!(vec!["11-01", "11-02", "11-03", "11-04", "11-05"], dates);
+    }
+
+    #[test]
+    fn timeline_chart_matches_puzzle() {
+        let profiles = example_profiles();
+        let minutes = active_hours(&profiles).unwrap();
+        assert_eq!("00:00", format_minute(minutes.start));
+        assert_eq!(60, minutes.len());
+
+        let mut chart = Vec::new();
+        write_timeline_chart(&mut chart, &profiles, minutes).unwrap();
+
+        let expected = "\
+Date   ID   Minute
+            000000000011111111112222222222333333333344444444445555555555
+            012345678901234567890123456789012345678901234567890123456789
+11-01  #10  .....####################.....#########################.....
+11-02  #99  ........................................##########..........
+11-03  #10  ........................#####...............................
+11-04  #99  ....................................##########..............
+11-05  #99  .............................................##########.....
+";
+        assert_eq!(expected, String::from_utf8(chart).unwrap());
+    }
+
+    #[test]
+    fn minute_matrix() {
+        let profiles = example_profiles();
+
+        let mut csv = Vec::new();
+        let midnight = minute_of_shift(Utc.ymd(1518, 11, 1).and_hms(0, 0, 0));
+        write_minute_matrix(&mut csv, &profiles, midnight + 20..midnight + 26).unwrap();
+
+        let expected = "\
+guard,00:20,00:21,00:22,00:23,00:24,00:25
+10,1,1,1,1,2,1
+99,0,0,0,0,0,0
+";
+        assert_eq!(expected, String::from_utf8(csv).unwrap());
+    }
 }
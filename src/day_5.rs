@@ -1,65 +1,359 @@
-fn react(a: char, b: char) -> bool {
-    a.to_ascii_uppercase() == b.to_ascii_uppercase()
-        && a.is_ascii_uppercase() != b.is_ascii_uppercase()
-}
-
-fn react_all(polymer: &str) -> String {
-    let mut chars: Vec<_> = polymer.chars().collect();
-
-    let mut pos = 0;
-    loop {
-        match chars.get(pos + 1).cloned() {
-            Some(next) => {
-                if react(chars[pos], next) {
-                    chars.remove(pos);
-                    chars.remove(pos);
-
-                    if pos > 0 {
-                        pos -= 1;
-                    }
-                } else {
-                    pos += 1;
+mod args;
+
+use {
+    std::{
+        collections::BTreeSet,
+        fs::File,
+        io::{self, BufReader, BufWriter, Read, Write},
+    },
+    rayon::prelude::*,
+};
+
+/// Which units destroy each other when they end up next to each other
+trait ReactionRule: Sync {
+    /// Whether `a` followed by `b` react
+    fn reacts(&self, a: u8, b: u8) -> bool;
+
+    /// The type of a unit. Removing a type of unit removes every unit of that type.
+    fn unit_type(&self, unit: u8) -> u8;
+
+    /// Whether removing a type of unit from the fully reacted polymer always leaves the same
+    /// result as removing it from the original, which saves reacting the whole polymer again
+    fn removal_after_reaction(&self) -> bool {
+        false
+    }
+}
+
+/// The puzzle's rule, where a letter reacts with the same letter in the opposite case and both
+/// cases are the same type
+struct OppositeCase;
+
+impl ReactionRule for OppositeCase {
+    fn reacts(&self, a: u8, b: u8) -> bool {
+        a != b && a.eq_ignore_ascii_case(&b)
+    }
+
+    fn unit_type(&self, unit: u8) -> u8 {
+        unit.to_ascii_lowercase()
+    }
+
+    // every reaction which happened with the removed type still happens without it, since
+    // removing a letter can't split up a pair which reacts or bring together one which doesn't
+    fn removal_after_reaction(&self) -> bool {
+        true
+    }
+}
+
+/// Reactions between any pairs of units, where the units in a pair are the same type
+struct PairRule {
+    // indexed by a * 256 + b
+    reactions: Vec<bool>,
+    types: Vec<u8>,
+}
+
+impl PairRule {
+    /// Each pair reacts in either order
+    fn symmetric(pairs: &[(u8, u8)]) -> Self {
+        let both_ways: Vec<_> = pairs.iter()
+            .flat_map(|&(a, b)| vec![(a, b), (b, a)])
+            .collect();
+
+        Self::directional(&both_ways)
+    }
+
+    /// Each pair only reacts when the first unit comes before the second
+    fn directional(pairs: &[(u8, u8)]) -> Self {
+        let mut reactions = vec![false; 256 * 256];
+        let mut types: Vec<u8> = (0..=255).collect();
+
+        for &(a, b) in pairs {
+            reactions[a as usize * 256 + b as usize] = true;
+
+            // merge the two types, keeping the smaller unit as the name of the type
+            let (type_a, type_b) = (types[a as usize], types[b as usize]);
+            let (from, to) = (type_a.max(type_b), type_a.min(type_b));
+            for unit_type in &mut types {
+                if *unit_type == from {
+                    *unit_type = to;
                 }
             }
-            None => break chars.into_iter().collect(),
         }
+
+        Self { reactions, types }
+    }
+
+    /// Parses pairs like `ab,cd`
+    fn parse_pairs(s: &str) -> Option<Vec<(u8, u8)>> {
+        s.split(',')
+            .map(|pair| match pair.trim().as_bytes() {
+                &[a, b] => Some((a, b)),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl ReactionRule for PairRule {
+    fn reacts(&self, a: u8, b: u8) -> bool {
+        self.reactions[a as usize * 256 + b as usize]
+    }
+
+    fn unit_type(&self, unit: u8) -> u8 {
+        self.types[unit as usize]
+    }
+}
+
+/// Adds a unit to the end of a fully reacted polymer. It can only react with the last unit,
+/// and if it does, the unit before that one is the new end and can react with the next unit
+/// added.
+fn add_unit(survivors: &mut Vec<u8>, unit: u8, rule: &impl ReactionRule) {
+    match survivors.last() {
+        Some(&last) if rule.reacts(last, unit) => {
+            survivors.pop();
+        }
+        _ => survivors.push(unit),
+    }
+}
+
+fn react_units(units: impl IntoIterator<Item=u8>, rule: &impl ReactionRule) -> Vec<u8> {
+    let mut survivors = Vec::new();
+    for unit in units {
+        add_unit(&mut survivors, unit, rule);
     }
+    survivors
 }
 
-fn remove_units(polymer: &str, unit: char) -> String {
-    let mut units: Vec<_> = polymer.chars().collect();
-    let mut i = 0;
-    while i < units.len() {
-        if units[i].to_ascii_lowercase() == unit {
-            units.remove(i);
-        } else {
-            i += 1;
+/// Reacts a polymer in one pass as it's read from `source`, ignoring whitespace such as a
+/// trailing newline
+fn react_all(source: impl Read, rule: &impl ReactionRule) -> io::Result<Vec<u8>> {
+    let mut survivors = Vec::new();
+    for unit in BufReader::new(source).bytes() {
+        let unit = unit?;
+        if !unit.is_ascii_whitespace() {
+            add_unit(&mut survivors, unit, rule);
         }
     }
+    Ok(survivors)
+}
 
-    units.into_iter().collect()
+/// Two units of the original polymer which destroyed each other, by their positions in the input
+#[derive(Debug, Copy, Clone)]
+struct Reaction {
+    left: usize,
+    right: usize,
+    /// 1 for units which were next to each other to begin with, otherwise one more than the
+    /// deepest pass of the reactions which brought them together
+    pass: usize,
+}
+
+/// Why a polymer reacted down to what it did
+struct Trace {
+    reactions: Vec<Reaction>,
+    /// The surviving units with their positions in the input
+    survivors: Vec<(usize, u8)>,
+}
+
+impl Trace {
+    fn passes(&self) -> usize {
+        self.reactions.iter().map(|reaction| reaction.pass).max().unwrap_or(0)
+    }
+
+    fn write(&self, out: &mut impl Write, input: &[u8]) -> io::Result<()> {
+        for reaction in &self.reactions {
+            writeln!(out, "pass {}: {} at {} reacted with {} at {}",
+                     reaction.pass,
+                     input[reaction.left] as char, reaction.left,
+                     input[reaction.right] as char, reaction.right)?;
+        }
+
+        for (index, unit) in &self.survivors {
+            writeln!(out, "survivor: {} at {}", *unit as char, index)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Reacts a polymer like `react_all`, also recording which units reacted with each other
+fn react_traced(source: impl Read, rule: &impl ReactionRule) -> io::Result<Trace> {
+    // each survivor remembers the deepest pass of the reactions between it and the survivor
+    // before it, and `deepest_since_top` is the same for the units after the last survivor
+    let mut survivors: Vec<(usize, u8, usize)> = Vec::new();
+    let mut deepest_since_top = 0;
+    let mut reactions = Vec::new();
+
+    for (index, unit) in BufReader::new(source).bytes().enumerate() {
+        let unit = unit?;
+        if unit.is_ascii_whitespace() {
+            continue;
+        }
+
+        match survivors.last() {
+            Some(&(last_index, last, deepest_before_last)) if rule.reacts(last, unit) => {
+                let pass = deepest_since_top + 1;
+                reactions.push(Reaction { left: last_index, right: index, pass });
+
+                survivors.pop();
+                deepest_since_top = usize::max(deepest_before_last, pass);
+            }
+            _ => {
+                survivors.push((index, unit, deepest_since_top));
+                deepest_since_top = 0;
+            }
+        }
+    }
+
+    Ok(Trace {
+        reactions,
+        survivors: survivors.into_iter().map(|(index, unit, _)| (index, unit)).collect(),
+    })
+}
+
+/// The length of the polymer after removing each type of unit in it and reacting again,
+/// ordered by unit type. `reacted` is the result of reacting all of `units`.
+fn removal_lengths(units: &[u8], reacted: &[u8], rule: &impl ReactionRule) -> Vec<(u8, usize)> {
+    let unit_types: BTreeSet<_> = units.iter().map(|unit| rule.unit_type(*unit)).collect();
+    let unit_types: Vec<_> = unit_types.into_iter().collect();
+
+    let source = if rule.removal_after_reaction() { reacted } else { units };
+
+    unit_types.into_par_iter()
+        .map(|unit_type| {
+            let without_type = source.iter().cloned()
+                .filter(|unit| rule.unit_type(*unit) != unit_type);
+
+            (unit_type, react_units(without_type, rule).len())
+        })
+        .collect()
+}
+
+fn run(polymer: &str, rule: &impl ReactionRule) {
+    let reacted = react_all(polymer.as_bytes(), rule).expect("failed to read polymer");
+    println!("base polymer length after reaction: {}", reacted.len());
+
+    if let Some(path) = args::value("--trace") {
+        let trace = react_traced(polymer.as_bytes(), rule).expect("failed to read polymer");
+        println!("{} reactions in {} passes, {} units survived",
+                 trace.reactions.len(), trace.passes(), trace.survivors.len());
+
+        let mut out = BufWriter::new(File::create(&path).expect("failed to create trace file"));
+        trace.write(&mut out, polymer.as_bytes()).expect("failed to write trace");
+        println!("saved reaction trace to {}", path);
+    }
+
+    let units: Vec<_> = polymer.bytes().filter(|unit| !unit.is_ascii_whitespace()).collect();
+    let lengths = removal_lengths(&units, &reacted, rule);
+    for (unit, len) in &lengths {
+        println!("length of polymer without unit {}: {}", *unit as char, len);
+    }
+
+    match lengths.iter().min_by_key(|(_unit, len)| *len) {
+        Some((removed_unit, shortest_len)) => {
+            println!("best unit to remove: {}, length: {}", *removed_unit as char, shortest_len);
+        }
+        None => println!("nothing left to remove"),
+    }
 }
 
 fn main() {
     let base_polymer = include_str!("day_5.txt");
-    println!("base polymer length after reaction: {}", react_all(base_polymer).len());
 
-    let simplified_polymers: Vec<_> = ('a' as u8..'z' as u8)
-        .map(|unit| unit as char)
-        .map(|unit| (unit, remove_units(base_polymer, unit)))
-        .collect();
+    // `--pairs ab,cd` replaces the puzzle's rule, and `--directional` makes `a` only react
+    // with a `b` after it
+    match args::value("--pairs") {
+        Some(pairs) => {
+            let pairs = PairRule::parse_pairs(&pairs).expect("pairs must look like ab,cd");
+            let rule = if args::flag("--directional") {
+                PairRule::directional(&pairs)
+            } else {
+                PairRule::symmetric(&pairs)
+            };
 
-    let mut reacted_polymers = Vec::new();
-    for (unit, polymer) in &simplified_polymers {
-        let reacted_polymer = react_all(polymer);
-        println!("length of polymer without unit {}: {}", unit, reacted_polymer.len());
+            run(base_polymer, &rule);
+        }
+        None => run(base_polymer, &OppositeCase),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EXAMPLE: &str = "dabAcCaCBAcCcaDA";
+
+    #[test]
+    fn puzzle_example() {
+        let reacted = react_all(EXAMPLE.as_bytes(), &OppositeCase).unwrap();
+        assert_eq!(b"dabCBAcaDA", &reacted[..]);
+
+        let lengths = removal_lengths(EXAMPLE.as_bytes(), &reacted, &OppositeCase);
+        assert_eq!(vec![(b'a', 6), (b'b', 8), (b'c', 4), (b'd', 6)], lengths);
+    }
+
+    #[test]
+    fn ignores_whitespace() {
+        let reacted = react_all("aA bB\nc\n".as_bytes(), &OppositeCase).unwrap();
+        assert_eq!(b"c", &reacted[..]);
+    }
+
+    #[test]
+    fn removes_types_which_react_away_entirely() {
+        let units = b"aAbc";
+        let reacted = react_units(units.iter().cloned(), &OppositeCase);
+
+        let lengths = removal_lengths(units, &reacted, &OppositeCase);
+        assert_eq!(vec![(b'a', 2), (b'b', 1), (b'c', 1)], lengths);
+    }
+
+    #[test]
+    fn pair_rules() {
+        let pairs = PairRule::parse_pairs("ab,cd").unwrap();
+        assert_eq!(vec![(b'a', b'b'), (b'c', b'd')], pairs);
+        assert_eq!(None, PairRule::parse_pairs("ab,c"));
+
+        let symmetric = PairRule::symmetric(&pairs);
+        assert_eq!(b"", &react_units(b"abbadc".iter().cloned(), &symmetric)[..]);
+        assert_eq!(b'a', symmetric.unit_type(b'b'));
+        assert_eq!(b'e', symmetric.unit_type(b'e'));
+
+        let directional = PairRule::directional(&pairs);
+        assert_eq!(b"ba", &react_units(b"abbacd".iter().cloned(), &directional)[..]);
+    }
+
+    #[test]
+    fn custom_rule_removal_reacts_the_original() {
+        // `a` and `e` are one type, and `b`, `c` and `d` another
+        let rule = PairRule::directional(&[(b'd', b'b'), (b'e', b'a'), (b'b', b'd'), (b'd', b'c')]);
+        let units = b"bedcdbd";
+        let reacted = react_units(units.iter().cloned(), &rule);
+        assert_eq!(b"bed", &reacted[..]);
 
-        reacted_polymers.push((unit, reacted_polymer));
+        // removing `e` from the reacted polymer would let `b` and `d` react, but in the
+        // original they were never next to each other
+        let lengths = removal_lengths(units, &reacted, &rule);
+        assert_eq!(vec![(b'a', 2), (b'b', 1)], lengths);
+
+        for (unit_type, len) in lengths {
+            let without_type = units.iter().cloned()
+                .filter(|unit| rule.unit_type(*unit) != unit_type);
+            assert_eq!(react_units(without_type, &rule).len(), len);
+        }
     }
 
-    let (removed_unit, shortest_polymer) = reacted_polymers.iter()
-        .min_by_key(|(_unit, polymer)| polymer.len())
-        .unwrap();
+    #[test]
+    fn traces_reactions() {
+        let trace = react_traced(EXAMPLE.as_bytes(), &OppositeCase).unwrap();
+
+        let reactions: Vec<_> = trace.reactions.iter()
+            .map(|reaction| (reaction.left, reaction.right, reaction.pass))
+            .collect();
+        assert_eq!(vec![(4, 5, 1), (3, 6, 2), (10, 11, 1)], reactions);
+        assert_eq!(2, trace.passes());
 
-    println!("best unit to remove: {}, length: {}", removed_unit, shortest_polymer.len());
-}
\ No newline at end of file
+        let survivors: Vec<_> = trace.survivors.iter().map(|(index, _)| *index).collect();
+        assert_eq!(vec![0, 1, 2, 7, 8, 9, 12, 13, 14, 15], survivors);
+
+        let units: Vec<_> = trace.survivors.iter().map(|(_, unit)| *unit).collect();
+        assert_eq!(b"dabCBAcaDA", &units[..]);
+    }
+}
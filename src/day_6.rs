@@ -1,10 +1,18 @@
 mod point;
+mod kdtree;
+mod voronoi;
+mod image;
+mod safe_region;
+mod args;
 
 use {
-    std::{
-        collections::hash_map::HashMap,
+    crate::{
+        point::{Point, Metric},
+        voronoi::{Extent, Regions, TiePolicy},
+        image::{Image, Palette, Rgb},
+        safe_region::SafeRegion,
     },
-    crate::point::{Point, BoundingBox},
+    std::collections::HashMap,
 };
 
 fn parse_coord(s: &str) -> Point {
@@ -17,65 +25,59 @@ fn parse_coord(s: &str) -> Point {
     }
 }
 
-fn main() {
-    let input = include_str!("day_6.txt");
-    let coords: Vec<_> = input.lines().map(parse_coord).collect();
-
-    let bounds = BoundingBox::of_points(coords.iter().cloned());
+/// Each region in its own colour, with infinite regions darkened and the coordinates in white
+fn regions_image(regions: &Regions, coords: &[Point]) -> Image {
+    let owners: HashMap<_, _> = regions.regions.iter()
+        .flat_map(|region| region.cells.iter().map(move |cell| (*cell, region)))
+        .collect();
 
-    let mut closest_coords_count = HashMap::new();
+    let palette = Palette::distinct(regions.regions.len());
 
-    for location in bounds.coords() {
-        let mut distances: Vec<_> = coords.iter()
-            .enumerate()
-            .map(|(i, coord)| (i, location.manhattan_dist_to(*coord)))
-            .collect();
+    Image::render(regions.area, 2, |pos| {
+        if coords.contains(&pos) {
+            return Rgb::WHITE;
+        }
 
-        distances.sort_unstable_by_key(|(_i, dist)| *dist);
+        match owners.get(&pos) {
+            Some(region) if region.finite => palette.get(region.seed),
+            Some(region) => palette.get(region.seed).lerp(Rgb::BLACK, 0.6),
+            None => Rgb::BLACK,
+        }
+    })
+}
 
-        let (closest_coord, closest_dist) = distances[0];
-        let (_next_coord, next_dist) = distances[1];
+fn main() {
+    let input = include_str!("day_6.txt");
+    let coords: Vec<_> = input.lines().map(parse_coord).collect();
 
-        if closest_dist != next_dist {
-            closest_coords_count.entry(closest_coord)
-                .or_insert_with(Vec::new)
-                .push(location);
-        }
-    }
+    // `--metric euclidean|chebyshev` and `--ties first|shared` change the puzzle's rules
+    let metric = args::value("--metric")
+        .map(|name| Metric::parse(&name).expect("metric must be manhattan, euclidean or chebyshev"))
+        .unwrap_or(Metric::Manhattan);
+    let tie_policy = args::value("--ties")
+        .map(|name| TiePolicy::parse(&name).expect("tie policy must be unclaimed, first or shared"))
+        .unwrap_or(TiePolicy::Unclaimed);
 
-    let infinite_coords: Vec<_> = closest_coords_count.iter()
-        .filter_map(|(coord, locs)| if locs.iter()
-            .any(|loc| bounds.on_edge(*loc)) {
-            Some(*coord)
-        } else {
-            None
-        })
-        .collect();
-    for infinite_coord in infinite_coords {
-        closest_coords_count.remove(&infinite_coord);
-    }
+    let regions = Regions::compute(&coords, metric, Extent::Exact, tie_policy)
+        .expect("input must contain at least one coordinate");
 
-    let largest = closest_coords_count.iter()
-        .max_by_key(|(_coord, locs)| locs.len())
-        .unwrap();
+    let largest = regions.largest_finite()
+        .expect("at least one coordinate must have a finite area");
 
-    println!("coord with largest area: #{} (count: {})", largest.0, largest.1.len());
+    println!("coord with largest area: #{} (count: {})", largest.seed, largest.size());
 
-    const SAFE_DIST: usize = 10000;
+    if let Some(path) = image::export_path() {
+        regions_image(&regions, &coords).save_ppm(&path).expect("failed to save image");
+        println!("saved regions to {}", path.display());
+    }
 
-    let safe_region_size = bounds.coords()
-        .filter_map(|location| {
-            let dist_to_all: usize = coords.iter()
-                .map(|coord| coord.manhattan_dist_to(location))
-                .sum();
+    // the puzzle's limit, which `--safe-dist` replaces
+    let safe_dist = args::value("--safe-dist")
+        .map(|dist| dist.parse().expect("safe distance must be a whole number"))
+        .unwrap_or(10000);
 
-            if dist_to_all < SAFE_DIST {
-                Some(location)
-            } else {
-                None
-            }
-        })
-        .count();
+    let safe_region = SafeRegion::compute(&coords, metric, safe_dist)
+        .expect("input must contain at least one coordinate");
 
-    println!("safe region where all coords are within {} size: {}", SAFE_DIST, safe_region_size);
+    println!("safe region where all coords are within {} size: {}", safe_dist, safe_region.size());
 }
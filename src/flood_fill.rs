@@ -0,0 +1,163 @@
+#![allow(unused)]
+
+use {
+    crate::point::{Grid, Point},
+    std::collections::VecDeque,
+};
+
+/// All the cells of `grid` reachable from `start` by stepping between passable cells, in the
+/// order they are reached. `start` itself is always included if it is inside the grid, so this
+/// also answers where something standing on an impassable cell could move to.
+///
+/// `neighbors` chooses the neighbourhood, e.g. `Point::neighbors_reading_order` or
+/// `Point::neighbors_moore`.
+pub fn flood_fill<T, N>(grid: &Grid<T>,
+                        start: Point,
+                        passable: impl Fn(Point, &T) -> bool,
+                        neighbors: impl Fn(Point) -> N) -> Vec<Point>
+    where N: Iterator<Item=Point>
+{
+    let mut visited = Grid::new(grid.width(), grid.height(), false);
+    let mut reached = Vec::new();
+
+    if grid.contains(start) {
+        visited[start] = true;
+        fill_from(grid, start, &passable, &neighbors, &mut visited, &mut reached);
+    }
+
+    reached
+}
+
+fn fill_from<T, N>(grid: &Grid<T>,
+                   start: Point,
+                   passable: &impl Fn(Point, &T) -> bool,
+                   neighbors: &impl Fn(Point) -> N,
+                   visited: &mut Grid<bool>,
+                   reached: &mut Vec<Point>)
+    where N: Iterator<Item=Point>
+{
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+
+    while let Some(next) = queue.pop_front() {
+        reached.push(next);
+
+        for neighbor in neighbors(next) {
+            let unvisited_passable = match grid.get(neighbor) {
+                Some(cell) => !visited[neighbor] && passable(neighbor, cell),
+                None => false,
+            };
+
+            if unvisited_passable {
+                visited[neighbor] = true;
+                queue.push_back(neighbor);
+            }
+        }
+    }
+}
+
+/// Separate regions of passable cells, labelled in the reading order of their first cell
+#[derive(Debug, Clone)]
+pub struct Components {
+    pub labels: Grid<Option<usize>>,
+    pub sizes: Vec<usize>,
+}
+
+impl Components {
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    pub fn label_at(&self, point: Point) -> Option<usize> {
+        self.labels.get(point).cloned().unwrap_or(None)
+    }
+
+    pub fn cells(&self, label: usize) -> impl Iterator<Item=Point> + '_ {
+        self.labels.iter()
+            .filter(move |(_, cell_label)| **cell_label == Some(label))
+            .map(|(point, _)| point)
+    }
+
+    /// The label and size of the largest component
+    pub fn largest(&self) -> Option<(usize, usize)> {
+        self.sizes.iter().cloned()
+            .enumerate()
+            .max_by_key(|(_, size)| *size)
+    }
+}
+
+pub fn connected_components<T, N>(grid: &Grid<T>,
+                                  passable: impl Fn(Point, &T) -> bool,
+                                  neighbors: impl Fn(Point) -> N) -> Components
+    where N: Iterator<Item=Point>
+{
+    let mut visited = Grid::new(grid.width(), grid.height(), false);
+    let mut labels = Grid::new(grid.width(), grid.height(), None);
+    let mut sizes = Vec::new();
+
+    let mut component = Vec::new();
+    for (point, cell) in grid.iter() {
+        if visited[point] || !passable(point, cell) {
+            continue;
+        }
+
+        visited[point] = true;
+        component.clear();
+        fill_from(grid, point, &passable, &neighbors, &mut visited, &mut component);
+
+        let label = sizes.len();
+        for member in &component {
+            labels[*member] = Some(label);
+        }
+        sizes.push(component.len());
+    }
+
+    Components { labels, sizes }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_grid() -> Grid<bool> {
+        let map = [
+            "..#..",
+            "..#.#",
+            "###..",
+            "#..#.",
+            ".#.#.",
+        ].join("\n");
+
+        Grid::parse(&map, |_, c| c == '.')
+    }
+
+    #[test]
+    fn fills_reachable_cells() {
+        let grid = test_grid();
+
+        let reached = flood_fill(&grid, Point::new(0, 0), |_, open| *open, Point::neighbors_reading_order);
+        assert_eq!(4, reached.len());
+        assert_eq!(Point::new(0, 0), reached[0]);
+
+        let blocked_start = flood_fill(&grid, Point::new(2, 0), |_, open| *open, Point::neighbors_reading_order);
+        assert_eq!(12, blocked_start.len());
+        assert_eq!(&[Point::new(2, 0), Point::new(1, 0), Point::new(3, 0)], &blocked_start[..3]);
+
+        assert!(flood_fill(&grid, Point::new(9, 9), |_, open| *open, Point::neighbors_reading_order).is_empty());
+    }
+
+    #[test]
+    fn labels_components_by_neighborhood() {
+        let grid = test_grid();
+
+        let orthogonal = connected_components(&grid, |_, open| *open, Point::neighbors_reading_order);
+        assert_eq!(vec![4, 7, 3, 1], orthogonal.sizes);
+        assert_eq!(Some(1), orthogonal.label_at(Point::new(4, 4)));
+        assert_eq!(None, orthogonal.label_at(Point::new(2, 0)));
+        assert_eq!(Some((1, 7)), orthogonal.largest());
+        assert_eq!(4, orthogonal.cells(0).count());
+
+        let moore = connected_components(&grid, |_, open| *open, Point::neighbors_moore);
+        assert_eq!(vec![4, 11], moore.sizes);
+    }
+}
@@ -0,0 +1,198 @@
+#![allow(unused)]
+
+use {
+    crate::{
+        args,
+        point::{BoundingBox, Point},
+    },
+    std::{
+        fs::File,
+        io::{self, BufWriter, Write},
+        path::{Path, PathBuf},
+    },
+};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb { r: 0, g: 0, b: 0 };
+    pub const WHITE: Rgb = Rgb { r: 255, g: 255, b: 255 };
+
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Rgb { r, g, b }
+    }
+
+    /// The colour `t` of the way from this colour to `other`, where `t` is between 0 and 1
+    pub fn lerp(self, other: Rgb, t: f32) -> Rgb {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+
+        Rgb::new(mix(self.r, other.r), mix(self.g, other.g), mix(self.b, other.b))
+    }
+
+    /// A fully saturated colour with the given hue, in turns from red
+    fn from_hue(hue: f32) -> Rgb {
+        let sector = (hue.rem_euclid(1.0) * 6.0) as usize;
+        let rise = hue.rem_euclid(1.0) * 6.0 - sector as f32;
+        let fall = 1.0 - rise;
+
+        let (r, g, b) = match sector {
+            0 => (1.0, rise, 0.0),
+            1 => (fall, 1.0, 0.0),
+            2 => (0.0, 1.0, rise),
+            3 => (0.0, fall, 1.0),
+            4 => (rise, 0.0, 1.0),
+            _ => (1.0, 0.0, fall),
+        };
+
+        Rgb::new((r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8)
+    }
+}
+
+/// A list of colours to pick from by index, e.g. for region labels or levels of a heatmap
+#[derive(Debug, Clone)]
+pub struct Palette {
+    colors: Vec<Rgb>,
+}
+
+impl Palette {
+    pub fn new(colors: Vec<Rgb>) -> Self {
+        assert!(!colors.is_empty(), "palette must have at least one colour");
+        Self { colors }
+    }
+
+    /// `n` colours with evenly spaced hues, for telling apart neighbouring regions
+    pub fn distinct(n: usize) -> Self {
+        let n = usize::max(n, 1);
+
+        // step around the colour wheel by a fraction coprime-ish to the count, so that
+        // consecutive indices don't get similar hues
+        let colors = (0..n)
+            .map(|i| Rgb::from_hue(i as f32 * 0.618_034))
+            .collect();
+
+        Self::new(colors)
+    }
+
+    /// `steps` colours blending evenly from `from` to `to`
+    pub fn gradient(from: Rgb, to: Rgb, steps: usize) -> Self {
+        let steps = usize::max(steps, 2);
+        let colors = (0..steps)
+            .map(|i| from.lerp(to, i as f32 / (steps - 1) as f32))
+            .collect();
+
+        Self::new(colors)
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// The colour at `index`, wrapping around if there are fewer colours than that
+    pub fn get(&self, index: usize) -> Rgb {
+        self.colors[index % self.colors.len()]
+    }
+}
+
+/// An RGB image, built by drawing each cell of some area as a square of pixels
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// Draws every cell in `bounds` as a `scale` by `scale` square in the colour `pixel_at` gives
+    pub fn render(bounds: BoundingBox, scale: usize, pixel_at: impl Fn(Point) -> Rgb) -> Self {
+        let scale = usize::max(scale, 1);
+        let width = bounds.width() * scale;
+        let height = bounds.height() * scale;
+
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in bounds.min().y..=bounds.max().y {
+            let row: Vec<_> = (bounds.min().x..=bounds.max().x)
+                .map(|x| pixel_at(Point::new(x, y)))
+                .collect();
+
+            for _ in 0..scale {
+                for pixel in &row {
+                    pixels.extend((0..scale).map(|_| *pixel));
+                }
+            }
+        }
+
+        Self { width, height, pixels }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Writes the image in the binary PPM (P6) format
+    pub fn write_ppm(&self, out: &mut impl Write) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+
+        let bytes: Vec<_> = self.pixels.iter()
+            .flat_map(|pixel| vec![pixel.r, pixel.g, pixel.b])
+            .collect();
+        out.write_all(&bytes)
+    }
+
+    pub fn save_ppm(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write_ppm(&mut out)?;
+        out.flush()
+    }
+}
+
+/// The file to export an image to, if one was given on the command line as `--ppm <path>`
+pub fn export_path() -> Option<PathBuf> {
+    args::value("--ppm").map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn writes_scaled_ppm() {
+        let bounds = BoundingBox::new(Point::new(-1, 0), Point::new(0, 0));
+        let image = Image::render(bounds, 2, |p| if p.x < 0 { Rgb::WHITE } else { Rgb::new(1, 2, 3) });
+
+        assert_eq!(4, image.width());
+        assert_eq!(2, image.height());
+
+        let mut out = Vec::new();
+        image.write_ppm(&mut out).unwrap();
+
+        let header = b"P6\n4 2\n255\n";
+        assert_eq!(&header[..], &out[..header.len()]);
+
+        let row = [255, 255, 255, 255, 255, 255, 1, 2, 3, 1, 2, 3];
+        assert_eq!([&row[..], &row[..]].concat(), &out[header.len()..]);
+    }
+
+    #[test]
+    fn palettes() {
+        let gradient = Palette::gradient(Rgb::BLACK, Rgb::new(200, 100, 0), 3);
+        assert_eq!(3, gradient.len());
+        assert_eq!(Rgb::new(100, 50, 0), gradient.get(1));
+        assert_eq!(Rgb::BLACK, gradient.get(3));
+
+        let distinct = Palette::distinct(6);
+        assert_eq!(Rgb::new(255, 0, 0), distinct.get(0));
+        for i in 1..6 {
+            assert_ne!(distinct.get(i - 1), distinct.get(i));
+        }
+    }
+}
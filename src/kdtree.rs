@@ -0,0 +1,262 @@
+#![allow(unused)]
+
+use {
+    crate::point::{Metric, Point},
+    std::cmp::Ordering,
+};
+
+/// A point found by a query, along with its index in the list the tree was built from and its
+/// `Metric::measure` from the query point
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Nearest {
+    pub index: usize,
+    pub point: Point,
+    pub measure: u128,
+}
+
+impl Nearest {
+    fn cmp_closest(&self, other: &Self) -> Ordering {
+        match self.measure.cmp(&other.measure) {
+            Ordering::Equal => self.index.cmp(&other.index),
+            order => order,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Entry {
+    index: usize,
+    point: Point,
+}
+
+/// A static 2D k-d tree for nearest-point and radius queries.
+///
+/// The tree is stored implicitly: the median of each range of `entries` is the node splitting
+/// that range, with the lower half of the range on its left and the upper half on its right.
+/// Nodes at even depths split on `x`, and at odd depths on `y`.
+pub struct KdTree {
+    entries: Vec<Entry>,
+}
+
+fn axis_value(point: Point, depth: usize) -> isize {
+    if depth % 2 == 0 {
+        point.x
+    } else {
+        point.y
+    }
+}
+
+impl KdTree {
+    pub fn new(points: &[Point]) -> Self {
+        let mut entries: Vec<_> = points.iter()
+            .enumerate()
+            .map(|(index, point)| Entry { index, point: *point })
+            .collect();
+
+        Self::build(&mut entries, 0);
+
+        Self { entries }
+    }
+
+    fn build(entries: &mut [Entry], depth: usize) {
+        if entries.len() <= 1 {
+            return;
+        }
+
+        let mid = entries.len() / 2;
+        entries.select_nth_unstable_by_key(mid, |entry| axis_value(entry.point, depth));
+
+        let (lower, upper) = entries.split_at_mut(mid);
+        Self::build(lower, depth + 1);
+        Self::build(&mut upper[1..], depth + 1);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn nearest(&self, target: Point, metric: Metric) -> Option<Nearest> {
+        self.nearest_n(target, 1, metric).pop()
+    }
+
+    /// The `n` points closest to `target`, closest first. Points at the same distance are
+    /// ordered by their index, so ties between the last places are settled the same way.
+    pub fn nearest_n(&self, target: Point, n: usize, metric: Metric) -> Vec<Nearest> {
+        let mut found = Vec::with_capacity(n + 1);
+        if n > 0 {
+            self.search_nearest(0, self.entries.len(), 0, target, n, metric, &mut found);
+        }
+        found
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn search_nearest(&self,
+                      start: usize,
+                      end: usize,
+                      depth: usize,
+                      target: Point,
+                      n: usize,
+                      metric: Metric,
+                      found: &mut Vec<Nearest>) {
+        if start >= end {
+            return;
+        }
+
+        let mid = start + (end - start) / 2;
+        let entry = self.entries[mid];
+
+        let candidate = Nearest {
+            index: entry.index,
+            point: entry.point,
+            measure: metric.measure(target, entry.point),
+        };
+
+        let insert_at = found.iter()
+            .position(|other| candidate.cmp_closest(other) == Ordering::Less)
+            .unwrap_or(found.len());
+        if insert_at < n {
+            found.insert(insert_at, candidate);
+            found.truncate(n);
+        }
+
+        let split = axis_value(entry.point, depth);
+        let target_value = axis_value(target, depth);
+
+        let (near, far) = if target_value < split {
+            ((start, mid), (mid + 1, end))
+        } else {
+            ((mid + 1, end), (start, mid))
+        };
+
+        self.search_nearest(near.0, near.1, depth + 1, target, n, metric, found);
+
+        // the far side can only hold closer points if the splitting line is closer than the
+        // worst point found so far
+        let split_measure = metric.axis_measure((split - target_value).unsigned_abs());
+        let far_may_be_closer = found.len() < n
+            || found.last().map(|worst| split_measure <= worst.measure).unwrap_or(true);
+
+        if far_may_be_closer {
+            self.search_nearest(far.0, far.1, depth + 1, target, n, metric, found);
+        }
+    }
+
+    /// All points no further than `radius` from `target`, closest first
+    pub fn within(&self, target: Point, radius: usize, metric: Metric) -> Vec<Nearest> {
+        let mut found = Vec::new();
+        let max_measure = metric.axis_measure(radius);
+
+        self.search_within(0, self.entries.len(), 0, target, max_measure, metric, &mut found);
+
+        found.sort_by(Nearest::cmp_closest);
+        found
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn search_within(&self,
+                     start: usize,
+                     end: usize,
+                     depth: usize,
+                     target: Point,
+                     max_measure: u128,
+                     metric: Metric,
+                     found: &mut Vec<Nearest>) {
+        if start >= end {
+            return;
+        }
+
+        let mid = start + (end - start) / 2;
+        let entry = self.entries[mid];
+
+        let measure = metric.measure(target, entry.point);
+        if measure <= max_measure {
+            found.push(Nearest { index: entry.index, point: entry.point, measure });
+        }
+
+        let split = axis_value(entry.point, depth);
+        let target_value = axis_value(target, depth);
+        let split_measure = metric.axis_measure((split - target_value).unsigned_abs());
+
+        if target_value < split || split_measure <= max_measure {
+            self.search_within(start, mid, depth + 1, target, max_measure, metric, found);
+        }
+        if target_value >= split || split_measure <= max_measure {
+            self.search_within(mid + 1, end, depth + 1, target, max_measure, metric, found);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const METRICS: [Metric; 3] = [Metric::Manhattan, Metric::Euclidean, Metric::Chebyshev];
+
+    fn test_points() -> Vec<Point> {
+        // a fixed pseudo-random scatter, with some duplicates to exercise ties
+        let mut seed = 12345u64;
+        let mut next = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((seed >> 33) % 40) as isize - 20
+        };
+
+        let mut points: Vec<_> = (0..60).map(|_| Point::new(next(), next())).collect();
+        points.push(points[3]);
+        points.push(points[17]);
+        points
+    }
+
+    fn brute_force(points: &[Point], target: Point, metric: Metric) -> Vec<Nearest> {
+        let mut all: Vec<_> = points.iter()
+            .enumerate()
+            .map(|(index, point)| Nearest { index, point: *point, measure: metric.measure(target, *point) })
+            .collect();
+        all.sort_by(Nearest::cmp_closest);
+        all
+    }
+
+    #[test]
+    fn empty_tree_finds_nothing() {
+        let tree = KdTree::new(&[]);
+
+        assert!(tree.is_empty());
+        assert_eq!(None, tree.nearest(Point::new(0, 0), Metric::Manhattan));
+        assert!(tree.within(Point::new(0, 0), 10, Metric::Euclidean).is_empty());
+    }
+
+    #[test]
+    fn nearest_matches_brute_force() {
+        let points = test_points();
+        let tree = KdTree::new(&points);
+
+        for metric in METRICS.iter().cloned() {
+            for target in Point::new(-25, -25).neighbors_moore().chain(points.iter().cloned()) {
+                let expected = brute_force(&points, target, metric);
+                let found = tree.nearest_n(target, 2, metric);
+
+                assert_eq!(&expected[..2], &found[..], "nearest two to {} by {}", target, metric);
+            }
+        }
+    }
+
+    #[test]
+    fn within_matches_brute_force() {
+        let points = test_points();
+        let tree = KdTree::new(&points);
+
+        for metric in METRICS.iter().cloned() {
+            for radius in 0..12 {
+                let target = Point::new(3, -2);
+                let expected: Vec<_> = brute_force(&points, target, metric).into_iter()
+                    .filter(|nearest| metric.dist(target, nearest.point) <= radius as f64)
+                    .collect();
+
+                assert_eq!(expected, tree.within(target, radius, metric), "radius {} by {}", radius, metric);
+            }
+        }
+    }
+}
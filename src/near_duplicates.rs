@@ -0,0 +1,287 @@
+#![allow(unused)]
+
+use {
+    levenshtein::levenshtein,
+    std::collections::HashMap,
+};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Distance {
+    /// The number of positions where two strings of the same length differ. Strings of
+    /// different lengths are never near each other.
+    Hamming,
+    /// The number of single letter insertions, deletions and substitutions to turn one string
+    /// into the other
+    Edit,
+}
+
+/// Two strings within the search distance of each other, `a` being the one earlier in the list
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NearPair {
+    pub a: usize,
+    pub b: usize,
+    pub distance: usize,
+    /// The letters the two strings have in common, in order
+    pub common: String,
+}
+
+/// Finds every pair of strings in `items` which are within `max_dist` of each other, ordered
+/// by their indices in `items`
+pub fn near_duplicates(items: &[&str], metric: Distance, max_dist: usize) -> Vec<NearPair> {
+    let mut pairs = match metric {
+        Distance::Hamming => hamming_pairs(items, max_dist),
+        Distance::Edit => edit_pairs(items, max_dist),
+    };
+
+    pairs.sort_by_key(|pair| (pair.a, pair.b));
+    pairs
+}
+
+fn hamming_distance(a: &[char], b: &[char]) -> usize {
+    a.iter().zip(b).filter(|(a, b)| a != b).count()
+}
+
+/// The range of letters in block `block` when splitting `len` letters into `blocks` blocks
+fn block_range(len: usize, blocks: usize, block: usize) -> (usize, usize) {
+    (len * block / blocks, len * (block + 1) / blocks)
+}
+
+/// Splits each string into `max_dist + 1` blocks. Two strings can't differ in every block, so
+/// any pair within the distance has at least one block in common, and only strings which share
+/// a block need to be compared.
+fn hamming_pairs(items: &[&str], max_dist: usize) -> Vec<NearPair> {
+    let chars: Vec<Vec<char>> = items.iter().map(|item| item.chars().collect()).collect();
+    let blocks = max_dist + 1;
+
+    let mut buckets: HashMap<(usize, usize, &[char]), Vec<usize>> = HashMap::new();
+    for (i, item) in chars.iter().enumerate() {
+        for block in 0..blocks {
+            let (start, end) = block_range(item.len(), blocks, block);
+            buckets.entry((item.len(), block, &item[start..end])).or_default().push(i);
+        }
+    }
+
+    let mut pairs = Vec::new();
+    for ((len, block, _), bucket) in &buckets {
+        for (n, &a) in bucket.iter().enumerate() {
+            for &b in &bucket[n + 1..] {
+                // a pair can share several blocks, so only report it from the first one
+                let shares_earlier_block = (0..*block).any(|earlier| {
+                    let (start, end) = block_range(*len, blocks, earlier);
+                    chars[a][start..end] == chars[b][start..end]
+                });
+                if shares_earlier_block {
+                    continue;
+                }
+
+                let distance = hamming_distance(&chars[a], &chars[b]);
+                if distance <= max_dist {
+                    let common = chars[a].iter().zip(&chars[b])
+                        .filter(|(a, b)| a == b)
+                        .map(|(a, _)| *a)
+                        .collect();
+
+                    pairs.push(NearPair { a, b, distance, common });
+                }
+            }
+        }
+    }
+
+    pairs
+}
+
+/// A BK-tree, where each child is stored under its distance to the parent. The triangle
+/// inequality means a query only has to visit children whose distance is within `max_dist`
+/// of the query's distance to the parent.
+struct BkTree<'a> {
+    items: &'a [&'a str],
+    // (item index, children as (distance, node index))
+    nodes: Vec<(usize, Vec<(usize, usize)>)>,
+}
+
+impl<'a> BkTree<'a> {
+    fn new(items: &'a [&'a str]) -> Self {
+        let mut tree = BkTree {
+            items,
+            nodes: Vec::with_capacity(items.len()),
+        };
+
+        for i in 0..items.len() {
+            tree.insert(i);
+        }
+        tree
+    }
+
+    fn insert(&mut self, item: usize) {
+        if self.nodes.is_empty() {
+            self.nodes.push((item, Vec::new()));
+            return;
+        }
+
+        let mut node = 0;
+        loop {
+            let distance = levenshtein(self.items[self.nodes[node].0], self.items[item]);
+            let child = self.nodes[node].1.iter()
+                .find(|(child_dist, _)| *child_dist == distance)
+                .map(|(_, child)| *child);
+
+            match child {
+                Some(child) => node = child,
+                None => {
+                    let new_node = self.nodes.len();
+                    self.nodes.push((item, Vec::new()));
+                    self.nodes[node].1.push((distance, new_node));
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Every item within `max_dist` of `target`, with its distance
+    fn within(&self, target: &str, max_dist: usize) -> Vec<(usize, usize)> {
+        let mut found = Vec::new();
+        let mut stack = if self.nodes.is_empty() { vec![] } else { vec![0] };
+
+        while let Some(node) = stack.pop() {
+            let (item, children) = &self.nodes[node];
+            let distance = levenshtein(self.items[*item], target);
+            if distance <= max_dist {
+                found.push((*item, distance));
+            }
+
+            stack.extend(children.iter()
+                .filter(|(child_dist, _)| {
+                    *child_dist + max_dist >= distance && *child_dist <= distance + max_dist
+                })
+                .map(|(_, child)| *child));
+        }
+
+        found
+    }
+}
+
+fn edit_pairs(items: &[&str], max_dist: usize) -> Vec<NearPair> {
+    let tree = BkTree::new(items);
+
+    (0..items.len())
+        .flat_map(|a| {
+            tree.within(items[a], max_dist).into_iter()
+                .filter(move |(b, _)| *b > a)
+                .map(move |(b, distance)| NearPair {
+                    a,
+                    b,
+                    distance,
+                    common: aligned_common_letters(items[a], items[b]),
+                })
+        })
+        .collect()
+}
+
+/// The letters kept in place by a cheapest series of edits turning `a` into `b`
+fn aligned_common_letters(a: &str, b: &str) -> String {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    // cost[i][j] is the edit distance between the first i letters of a and the first j of b
+    let mut cost = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for i in 0..=a.len() {
+        for j in 0..=b.len() {
+            cost[i][j] = match (i, j) {
+                (0, j) => j,
+                (i, 0) => i,
+                (i, j) => {
+                    let substitute = cost[i - 1][j - 1] + if a[i - 1] == b[j - 1] { 0 } else { 1 };
+                    substitute.min(cost[i - 1][j] + 1).min(cost[i][j - 1] + 1)
+                }
+            };
+        }
+    }
+
+    let mut common = Vec::new();
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > 0 && j > 0 {
+        if a[i - 1] == b[j - 1] && cost[i][j] == cost[i - 1][j - 1] {
+            common.push(a[i - 1]);
+            i -= 1;
+            j -= 1;
+        } else if cost[i][j] == cost[i - 1][j - 1] + 1 {
+            i -= 1;
+            j -= 1;
+        } else if cost[i][j] == cost[i - 1][j] + 1 {
+            i -= 1;
+        } else {
+            j -= 1;
+        }
+    }
+
+    common.iter().rev().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const IDS: &[&str] = &["abcde", "fghij", "klmno", "pqrst", "fguij", "axcye", "wvxyz", "fgij"];
+
+    fn brute_force(items: &[&str], metric: Distance, max_dist: usize) -> Vec<(usize, usize, usize)> {
+        let mut pairs = Vec::new();
+        for a in 0..items.len() {
+            for b in a + 1..items.len() {
+                let distance = match metric {
+                    Distance::Edit => levenshtein(items[a], items[b]),
+                    Distance::Hamming if items[a].len() == items[b].len() => {
+                        items[a].chars().zip(items[b].chars()).filter(|(a, b)| a != b).count()
+                    }
+                    Distance::Hamming => continue,
+                };
+
+                if distance <= max_dist {
+                    pairs.push((a, b, distance));
+                }
+            }
+        }
+        pairs
+    }
+
+    #[test]
+    fn puzzle_example() {
+        let pairs = near_duplicates(IDS, Distance::Hamming, 1);
+        assert_eq!(vec![NearPair { a: 1, b: 4, distance: 1, common: "fgij".to_string() }], pairs);
+
+        let pairs = near_duplicates(IDS, Distance::Edit, 1);
+        let found: Vec<_> = pairs.iter().map(|pair| (pair.a, pair.b, &pair.common[..])).collect();
+        assert_eq!(vec![(1, 4, "fgij"), (1, 7, "fgij"), (4, 7, "fgij")], found);
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        // every string of length 3 or 4 over a small alphabet, so there are lots of near pairs
+        let mut items = Vec::new();
+        for n in 0..81 + 27 {
+            let (len, mut n) = if n < 81 { (4, n) } else { (3, n - 81) };
+            let mut item = String::new();
+            for _ in 0..len {
+                item.push(['a', 'b', 'c'][n % 3]);
+                n /= 3;
+            }
+            items.push(item);
+        }
+        let items: Vec<&str> = items.iter().map(String::as_str).collect();
+
+        for &metric in &[Distance::Hamming, Distance::Edit] {
+            for max_dist in 0..=3 {
+                let found: Vec<_> = near_duplicates(&items, metric, max_dist).iter()
+                    .map(|pair| (pair.a, pair.b, pair.distance))
+                    .collect();
+                assert_eq!(brute_force(&items, metric, max_dist), found, "{:?} {}", metric, max_dist);
+            }
+        }
+    }
+
+    #[test]
+    fn common_letters_follow_alignment() {
+        assert_eq!("abd", aligned_common_letters("abcd", "abd"));
+        assert_eq!("bcd", aligned_common_letters("xbcd", "bcdy"));
+        assert_eq!("", aligned_common_letters("", "abc"));
+    }
+}
@@ -2,10 +2,9 @@
 
 use {
     std::{
-        ops::Add,
+        ops::{Add, AddAssign, Sub, SubAssign, Neg, Mul, Index, IndexMut},
         cmp::Ordering,
         fmt,
-        usize,
     },
 };
 
@@ -15,37 +14,112 @@ pub struct Point {
     pub y: isize,
 }
 
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    /// All directions, clockwise from north
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// All directions, in the reading order of the neighbors they lead to
+    pub const READING_ORDER: [Direction; 4] = [
+        Direction::North,
+        Direction::West,
+        Direction::East,
+        Direction::South,
+    ];
+
+    pub fn offset(self) -> Point {
+        match self {
+            Direction::North => Point::new(0, -1),
+            Direction::East => Point::new(1, 0),
+            Direction::South => Point::new(0, 1),
+            Direction::West => Point::new(-1, 0),
+        }
+    }
+
+    pub fn turn_left(self) -> Self {
+        match self {
+            Direction::North => Direction::West,
+            Direction::West => Direction::South,
+            Direction::South => Direction::East,
+            Direction::East => Direction::North,
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+
+    pub fn reverse(self) -> Self {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+
+    pub fn is_vertical(self) -> bool {
+        match self {
+            Direction::North | Direction::South => true,
+            Direction::East | Direction::West => false,
+        }
+    }
+}
+
+impl From<Direction> for Point {
+    fn from(dir: Direction) -> Point {
+        dir.offset()
+    }
+}
+
 #[allow(unused)]
 impl Point {
-    const NORTH: Point = Point { x: 0, y: -1 };
-    const SOUTH: Point = Point { x: 0, y: 1 };
-    const EAST: Point = Point { x: 1, y: 0 };
-    const WEST: Point = Point { x: -1, y: 0 };
-
     pub fn new(x: isize, y: isize) -> Self {
         Point { x, y }
     }
 
     pub fn north(self) -> Self {
-        self + Self::NORTH
+        self + Direction::North
     }
 
     pub fn south(self) -> Self {
-        self + Self::SOUTH
+        self + Direction::South
     }
 
     pub fn east(self) -> Self {
-        self + Self::EAST
+        self + Direction::East
     }
 
     pub fn west(self) -> Self {
-        self + Self::WEST
+        self + Direction::West
     }
 
     pub fn neighbors_reading_order(self) -> Neighbors {
         Neighbors { point: self, dir: 0 }
     }
 
+    /// All eight surrounding points including diagonals, in reading order
+    pub fn neighbors_moore(self) -> MooreNeighbors {
+        MooreNeighbors { point: self, offset: 0 }
+    }
+
     pub fn cmp_reading_order(self, other: Self) -> Ordering {
         match self.y.cmp(&other.y) {
             Ordering::Equal => self.x.cmp(&other.x),
@@ -65,6 +139,14 @@ impl Point {
 
         dist_x as usize + dist_y as usize
     }
+
+    /// Number of king's moves between two points, the distance metric matching `neighbors_moore`
+    pub fn chebyshev_dist_to(self, other: Self) -> usize {
+        let dist_x = (self.x - other.x).abs();
+        let dist_y = (self.y - other.y).abs();
+
+        isize::max(dist_x, dist_y) as usize
+    }
 }
 
 impl Add for Point {
@@ -75,12 +157,124 @@ impl Add for Point {
     }
 }
 
+impl Add<Direction> for Point {
+    type Output = Self;
+
+    fn add(self, rhs: Direction) -> Self {
+        self + rhs.offset()
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl AddAssign<Direction> for Point {
+    fn add_assign(&mut self, rhs: Direction) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Point::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<isize> for Point {
+    type Output = Self;
+
+    fn mul(self, rhs: isize) -> Self {
+        Point::new(self.x * rhs, self.y * rhs)
+    }
+}
+
 impl fmt::Display for Point {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "({}, {})", self.x, self.y)
     }
 }
 
+/// A way of measuring the distance between two points
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Metric {
+    Manhattan,
+    Euclidean,
+    Chebyshev,
+}
+
+impl Metric {
+    /// Parses the lowercase name of a metric, as it's displayed
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "manhattan" => Some(Metric::Manhattan),
+            "euclidean" => Some(Metric::Euclidean),
+            "chebyshev" => Some(Metric::Chebyshev),
+            _ => None,
+        }
+    }
+
+    /// A whole-number measure of the distance between two points which orders pairs of points
+    /// the same way as the distance itself. For the euclidean metric this is the squared
+    /// distance, which unlike the distance can be compared exactly. It's kept in a `u128` so the
+    /// square can't overflow for any points whose coordinates differ by less than `2^63`
+    pub fn measure(self, a: Point, b: Point) -> u128 {
+        match self {
+            Metric::Manhattan => a.manhattan_dist_to(b) as u128,
+            Metric::Chebyshev => a.chebyshev_dist_to(b) as u128,
+            Metric::Euclidean => {
+                let dist_x = (a.x - b.x).unsigned_abs() as u128;
+                let dist_y = (a.y - b.y).unsigned_abs() as u128;
+                dist_x * dist_x + dist_y * dist_y
+            }
+        }
+    }
+
+    /// The measure of two points `dist` apart along a single axis
+    pub fn axis_measure(self, dist: usize) -> u128 {
+        let dist = dist as u128;
+        match self {
+            Metric::Manhattan | Metric::Chebyshev => dist,
+            Metric::Euclidean => dist * dist,
+        }
+    }
+
+    pub fn dist(self, a: Point, b: Point) -> f64 {
+        match self {
+            Metric::Euclidean => (self.measure(a, b) as f64).sqrt(),
+            Metric::Manhattan | Metric::Chebyshev => self.measure(a, b) as f64,
+        }
+    }
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", match self {
+            Metric::Manhattan => "manhattan",
+            Metric::Euclidean => "euclidean",
+            Metric::Chebyshev => "chebyshev",
+        })
+    }
+}
+
 pub struct Neighbors {
     point: Point,
     dir: usize,
@@ -90,39 +284,162 @@ impl Iterator for Neighbors {
     type Item = Point;
 
     fn next(&mut self) -> Option<Point> {
-        let next = match self.dir {
-            0 => Some(self.point.north()),
-            1 => Some(self.point.west()),
-            2 => Some(self.point.east()),
-            3 => Some(self.point.south()),
-            _ => None,
-        };
+        let next = Direction::READING_ORDER.get(self.dir)
+            .map(|dir| self.point + *dir);
         self.dir += 1;
         next
     }
 }
 
-#[derive(Debug)]
+pub struct MooreNeighbors {
+    point: Point,
+    offset: usize,
+}
+
+impl Iterator for MooreNeighbors {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        // walk the 3x3 block around the point, skipping the point itself in the middle
+        if self.offset == 4 {
+            self.offset += 1;
+        }
+
+        if self.offset >= 9 {
+            return None;
+        }
+
+        let x = (self.offset % 3) as isize - 1;
+        let y = (self.offset / 3) as isize - 1;
+        self.offset += 1;
+
+        Some(self.point + Point::new(x, y))
+    }
+}
+
+/// A cell of a hex grid in axial coordinates. Rows run along `r`, and `q` increases to the
+/// east within a row, so the six neighbors are east, west, north-east, north-west,
+/// south-east and south-west
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct HexPoint {
+    pub q: isize,
+    pub r: isize,
+}
+
+impl HexPoint {
+    const OFFSETS_READING_ORDER: [(isize, isize); 6] = [
+        (0, -1),
+        (1, -1),
+        (-1, 0),
+        (1, 0),
+        (-1, 1),
+        (0, 1),
+    ];
+
+    pub fn new(q: isize, r: isize) -> Self {
+        HexPoint { q, r }
+    }
+
+    pub fn neighbors_reading_order(self) -> HexNeighbors {
+        HexNeighbors { point: self, dir: 0 }
+    }
+
+    pub fn cmp_reading_order(self, other: Self) -> Ordering {
+        match self.r.cmp(&other.r) {
+            Ordering::Equal => self.q.cmp(&other.q),
+            order => order,
+        }
+    }
+
+    /// Number of steps between two cells of the hex grid
+    pub fn hex_dist_to(self, other: Self) -> usize {
+        let dist_q = (self.q - other.q).abs();
+        let dist_r = (self.r - other.r).abs();
+        let dist_s = (self.q + self.r - other.q - other.r).abs();
+
+        (dist_q + dist_r + dist_s) as usize / 2
+    }
+}
+
+impl Add for HexPoint {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        HexPoint::new(self.q + rhs.q, self.r + rhs.r)
+    }
+}
+
+impl fmt::Display for HexPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<{}, {}>", self.q, self.r)
+    }
+}
+
+pub struct HexNeighbors {
+    point: HexPoint,
+    dir: usize,
+}
+
+impl Iterator for HexNeighbors {
+    type Item = HexPoint;
+
+    fn next(&mut self) -> Option<HexPoint> {
+        let next = HexPoint::OFFSETS_READING_ORDER.get(self.dir)
+            .map(|&(q, r)| self.point + HexPoint::new(q, r));
+        self.dir += 1;
+        next
+    }
+}
+
+/// An inclusive, non-empty rectangle of points. Boxes of nothing are represented as
+/// `Option<BoundingBox>` by the constructors and operations which can produce them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct BoundingBox {
     min: Point,
     max: Point,
 }
 
 impl BoundingBox {
-    pub fn of_points(mut points: impl Iterator<Item=Point>) -> BoundingBox {
-        let (mut min, mut max) = {
-            let first = points.next().unwrap();
-            (first.clone(), first.clone())
-        };
+    /// Creates the box spanning two corners, which may be given in any order
+    pub fn new(a: Point, b: Point) -> Self {
+        BoundingBox {
+            min: Point::new(isize::min(a.x, b.x), isize::min(a.y, b.y)),
+            max: Point::new(isize::max(a.x, b.x), isize::max(a.y, b.y)),
+        }
+    }
+
+    pub fn of_point(point: Point) -> Self {
+        BoundingBox { min: point, max: point }
+    }
 
-        while let Some(point) = points.next() {
-            min.x = isize::min(point.x, min.x);
-            min.y = isize::min(point.y, min.y);
-            max.x = isize::max(point.x, max.x);
-            max.y = isize::max(point.y, max.y);
+    /// The smallest box containing all the points, or `None` if there are no points
+    pub fn of_points(mut points: impl Iterator<Item=Point>) -> Option<BoundingBox> {
+        let mut bounds = BoundingBox::of_point(points.next()?);
+        for point in points {
+            bounds.extend(point);
         }
 
-        BoundingBox { min, max }
+        Some(bounds)
+    }
+
+    pub fn min(&self) -> Point {
+        self.min
+    }
+
+    pub fn max(&self) -> Point {
+        self.max
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.x - self.min.x) as usize + 1
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.y - self.min.y) as usize + 1
+    }
+
+    pub fn area(&self) -> usize {
+        self.width() * self.height()
     }
 
     pub fn on_edge(&self, coord: Point) -> bool {
@@ -132,10 +449,11 @@ impl BoundingBox {
             || coord.y == self.max.y
     }
 
+    /// All points inside the box, in reading order
     pub fn coords(&self) -> impl Iterator<Item=Point> + '_ {
-        (self.min.x..=self.max.x)
-            .flat_map(move |x| {
-                (self.min.y..=self.max.y).map(move |y| {
+        (self.min.y..=self.max.y)
+            .flat_map(move |y| {
+                (self.min.x..=self.max.x).map(move |x| {
                     Point { x, y }
                 })
             })
@@ -147,4 +465,583 @@ impl BoundingBox {
             && point.y >= self.min.y
             && point.y <= self.max.y
     }
-}
\ No newline at end of file
+
+    /// Grows the box just enough to contain `point`
+    pub fn extend(&mut self, point: Point) {
+        self.min.x = isize::min(point.x, self.min.x);
+        self.min.y = isize::min(point.y, self.min.y);
+        self.max.x = isize::max(point.x, self.max.x);
+        self.max.y = isize::max(point.y, self.max.y);
+    }
+
+    /// The smallest box containing both boxes
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = *self;
+        union.extend(other.min);
+        union.extend(other.max);
+        union
+    }
+
+    /// The points contained in both boxes, or `None` if they don't overlap
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min = Point::new(isize::max(self.min.x, other.min.x), isize::max(self.min.y, other.min.y));
+        let max = Point::new(isize::min(self.max.x, other.max.x), isize::min(self.max.y, other.max.y));
+
+        if min.x <= max.x && min.y <= max.y {
+            Some(BoundingBox { min, max })
+        } else {
+            None
+        }
+    }
+
+    /// Pads the box by `n` points on every side
+    pub fn expand(&self, n: usize) -> Self {
+        let padding = Point::new(n as isize, n as isize);
+        BoundingBox {
+            min: self.min - padding,
+            max: self.max + padding,
+        }
+    }
+}
+
+/// A point in N-dimensional space. For reading order the last axis is the most significant,
+/// the same way `y` outranks `x` for a 2D `Point`.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct PointN<const N: usize> {
+    pub coords: [isize; N],
+}
+
+pub type Point3 = PointN<3>;
+pub type Point4 = PointN<4>;
+
+impl<const N: usize> PointN<N> {
+    pub fn new(coords: [isize; N]) -> Self {
+        Self { coords }
+    }
+
+    pub fn origin() -> Self {
+        Self { coords: [0; N] }
+    }
+
+    /// The 2N orthogonal neighbors of this point, in reading order
+    pub fn neighbors_reading_order(self) -> NeighborsN<N> {
+        NeighborsN { point: self, step: 0 }
+    }
+
+    pub fn cmp_reading_order(self, other: Self) -> Ordering {
+        self.coords.iter().rev()
+            .zip(other.coords.iter().rev())
+            .map(|(a, b)| a.cmp(b))
+            .find(|order| *order != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    }
+
+    pub fn manhattan_dist_to(self, other: Self) -> usize {
+        self.coords.iter()
+            .zip(other.coords.iter())
+            .map(|(a, b)| (a - b).unsigned_abs())
+            .sum()
+    }
+
+    pub fn manhattan_len(self) -> usize {
+        self.manhattan_dist_to(Self::origin())
+    }
+}
+
+impl<const N: usize> Index<usize> for PointN<N> {
+    type Output = isize;
+
+    fn index(&self, axis: usize) -> &isize {
+        &self.coords[axis]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for PointN<N> {
+    fn index_mut(&mut self, axis: usize) -> &mut isize {
+        &mut self.coords[axis]
+    }
+}
+
+impl<const N: usize> Add for PointN<N> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        for axis in 0..N {
+            self.coords[axis] += rhs.coords[axis];
+        }
+        self
+    }
+}
+
+impl<const N: usize> AddAssign for PointN<N> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<const N: usize> Sub for PointN<N> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl<const N: usize> SubAssign for PointN<N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<const N: usize> Neg for PointN<N> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self * -1
+    }
+}
+
+impl<const N: usize> Mul<isize> for PointN<N> {
+    type Output = Self;
+
+    fn mul(mut self, rhs: isize) -> Self {
+        for coord in self.coords.iter_mut() {
+            *coord *= rhs;
+        }
+        self
+    }
+}
+
+impl<const N: usize> fmt::Display for PointN<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(")?;
+        for (axis, coord) in self.coords.iter().enumerate() {
+            if axis > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", coord)?;
+        }
+        write!(f, ")")
+    }
+}
+
+pub struct NeighborsN<const N: usize> {
+    point: PointN<N>,
+    step: usize,
+}
+
+impl<const N: usize> Iterator for NeighborsN<N> {
+    type Item = PointN<N>;
+
+    fn next(&mut self) -> Option<PointN<N>> {
+        // negative steps along the most significant axis come first, positive steps along it last
+        let (axis, offset) = if self.step < N {
+            (N - 1 - self.step, -1)
+        } else if self.step < N * 2 {
+            (self.step - N, 1)
+        } else {
+            return None;
+        };
+
+        self.step += 1;
+
+        let mut neighbor = self.point;
+        neighbor.coords[axis] += offset;
+        Some(neighbor)
+    }
+}
+
+#[derive(Debug)]
+pub struct BoundingBoxN<const N: usize> {
+    min: PointN<N>,
+    max: PointN<N>,
+}
+
+pub type BoundingBox3 = BoundingBoxN<3>;
+pub type BoundingBox4 = BoundingBoxN<4>;
+
+impl<const N: usize> BoundingBoxN<N> {
+    pub fn of_points(mut points: impl Iterator<Item=PointN<N>>) -> Option<Self> {
+        let first = points.next()?;
+        let (mut min, mut max) = (first, first);
+
+        for point in points {
+            for axis in 0..N {
+                min.coords[axis] = isize::min(point.coords[axis], min.coords[axis]);
+                max.coords[axis] = isize::max(point.coords[axis], max.coords[axis]);
+            }
+        }
+
+        Some(Self { min, max })
+    }
+
+    pub fn min(&self) -> PointN<N> {
+        self.min
+    }
+
+    pub fn max(&self) -> PointN<N> {
+        self.max
+    }
+
+    pub fn on_edge(&self, point: PointN<N>) -> bool {
+        (0..N).any(|axis| {
+            point.coords[axis] == self.min.coords[axis]
+                || point.coords[axis] == self.max.coords[axis]
+        })
+    }
+
+    pub fn contains(&self, point: PointN<N>) -> bool {
+        (0..N).all(|axis| {
+            point.coords[axis] >= self.min.coords[axis]
+                && point.coords[axis] <= self.max.coords[axis]
+        })
+    }
+
+    /// All points inside the box, in reading order
+    pub fn coords(&self) -> impl Iterator<Item=PointN<N>> + '_ {
+        let mut next = Some(self.min);
+
+        std::iter::from_fn(move || {
+            let current = next?;
+
+            // advance like an odometer, with the least significant axis turning fastest
+            let mut following = current;
+            next = (0..N)
+                .find(|&axis| {
+                    if following.coords[axis] < self.max.coords[axis] {
+                        following.coords[axis] += 1;
+                        true
+                    } else {
+                        following.coords[axis] = self.min.coords[axis];
+                        false
+                    }
+                })
+                .map(|_| following);
+
+            Some(current)
+        })
+    }
+}
+
+/// A dense, fixed-size 2D grid of cells indexed by `Point`, with the origin at (0, 0)
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+        where T: Clone
+    {
+        Self {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    pub fn from_fn(width: usize, height: usize, mut cell: impl FnMut(Point) -> T) -> Self {
+        let cells = (0..height as isize)
+            .flat_map(|y| (0..width as isize).map(move |x| Point::new(x, y)))
+            .map(cell)
+            .collect();
+
+        Self { cells, width, height }
+    }
+
+    /// Parses an ASCII map, one row per line. The grid is as wide as the longest line, and
+    /// cells past the end of shorter lines are read as spaces.
+    pub fn parse(s: &str, mut cell: impl FnMut(Point, char) -> T) -> Self {
+        let width = s.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+        let height = s.lines().count();
+
+        let mut cells = Vec::with_capacity(width * height);
+        for (y, line) in s.lines().enumerate() {
+            let mut chars = line.chars();
+            for x in 0..width {
+                let point = Point::new(x as isize, y as isize);
+                cells.push(cell(point, chars.next().unwrap_or(' ')));
+            }
+        }
+
+        Self { cells, width, height }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The area covered by the grid, or `None` if it has no cells
+    pub fn bounds(&self) -> Option<BoundingBox> {
+        if self.width == 0 || self.height == 0 {
+            None
+        } else {
+            let max = Point::new(self.width as isize - 1, self.height as isize - 1);
+            Some(BoundingBox::new(Point::new(0, 0), max))
+        }
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.x >= 0
+            && point.y >= 0
+            && (point.x as usize) < self.width
+            && (point.y as usize) < self.height
+    }
+
+    fn index_of(&self, point: Point) -> Option<usize> {
+        if self.contains(point) {
+            Some(point.y as usize * self.width + point.x as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.index_of(point).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        match self.index_of(point) {
+            Some(i) => Some(&mut self.cells[i]),
+            None => None,
+        }
+    }
+
+    /// All points in the grid, in reading order
+    pub fn points(&self) -> impl Iterator<Item=Point> {
+        let width = self.width as isize;
+        (0..self.height as isize)
+            .flat_map(move |y| (0..width).map(move |x| Point::new(x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=(Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item=(Point, &mut T)> {
+        self.points().zip(self.cells.iter_mut())
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item=&[T]> {
+        (0..self.height).map(move |y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item=&T> {
+        assert!(x < self.width, "column {} out of range for grid of width {}", x, self.width);
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item=impl Iterator<Item=&T>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// The orthogonal neighbors of `point` which lie inside the grid, in reading order
+    pub fn neighbors(&self, point: Point) -> impl Iterator<Item=(Point, &T)> {
+        point.neighbors_reading_order()
+            .filter_map(move |neighbor| self.get(neighbor).map(|cell| (neighbor, cell)))
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        match self.index_of(point) {
+            Some(i) => &self.cells[i],
+            None => panic!("point {} out of range for {}x{} grid", point, self.width, self.height),
+        }
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        match self.index_of(point) {
+            Some(i) => &mut self.cells[i],
+            None => panic!("point {} out of range for {}x{} grid", point, self.width, self.height),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn turns_around_compass() {
+        for dir in Direction::ALL.iter().cloned() {
+            assert_eq!(dir.reverse(), dir.turn_left().turn_left());
+            assert_eq!(dir, dir.turn_right().turn_left());
+            assert_eq!(-dir.offset(), dir.reverse().offset());
+        }
+
+        assert_eq!(Direction::East, Direction::North.turn_right());
+        assert_eq!(Direction::West, Direction::North.turn_left());
+    }
+
+    #[test]
+    fn point_arithmetic() {
+        let mut point = Point::new(2, 3);
+        point += Direction::South;
+        assert_eq!(Point::new(2, 4), point);
+
+        point -= Point::new(1, 1);
+        assert_eq!(Point::new(1, 3), point);
+        assert_eq!(Point::new(-3, -9), -point * 3);
+        assert_eq!(Point::new(4, 3), point + Direction::East.offset() * 3);
+        assert_eq!(Point::new(0, 0), point - point);
+    }
+
+    #[test]
+    fn point_n_neighbors_in_reading_order() {
+        let neighbors: Vec<_> = Point3::origin().neighbors_reading_order().collect();
+
+        assert_eq!(vec![
+            Point3::new([0, 0, -1]),
+            Point3::new([0, -1, 0]),
+            Point3::new([-1, 0, 0]),
+            Point3::new([1, 0, 0]),
+            Point3::new([0, 1, 0]),
+            Point3::new([0, 0, 1]),
+        ], neighbors);
+
+        let mut sorted = neighbors.clone();
+        sorted.sort_by(|a, b| a.cmp_reading_order(*b));
+        assert_eq!(neighbors, sorted);
+    }
+
+    #[test]
+    fn point_n_bounding_box() {
+        let points = [Point4::new([0, 0, 0, 0]), Point4::new([3, 0, 0, 0]), Point4::new([0, 0, 0, 6])];
+        let bounds = BoundingBox4::of_points(points.iter().cloned()).unwrap();
+
+        assert_eq!(4 * 7, bounds.coords().count());
+        assert!(bounds.coords().all(|p| bounds.contains(p)));
+        assert!(!bounds.contains(Point4::new([0, 1, 0, 0])));
+        assert_eq!(9, points[1].manhattan_dist_to(points[2]));
+        assert!(BoundingBox3::of_points(std::iter::empty()).is_none());
+    }
+
+    #[test]
+    fn moore_neighbors_in_reading_order() {
+        let center = Point::new(5, 5);
+        let neighbors: Vec<_> = center.neighbors_moore().collect();
+
+        assert_eq!(8, neighbors.len());
+        assert!(neighbors.iter().all(|n| center.chebyshev_dist_to(*n) == 1));
+
+        let mut sorted = neighbors.clone();
+        sorted.sort_by(|a, b| a.cmp_reading_order(*b));
+        assert_eq!(neighbors, sorted);
+    }
+
+    #[test]
+    fn hex_neighbors_are_one_step_away() {
+        let center = HexPoint::new(2, -3);
+        let neighbors: Vec<_> = center.neighbors_reading_order().collect();
+
+        assert_eq!(6, neighbors.len());
+        assert!(neighbors.iter().all(|n| center.hex_dist_to(*n) == 1));
+
+        let mut sorted = neighbors.clone();
+        sorted.sort_by(|a, b| a.cmp_reading_order(*b));
+        assert_eq!(neighbors, sorted);
+
+        assert_eq!(3, HexPoint::new(0, 0).hex_dist_to(HexPoint::new(3, -3)));
+        assert_eq!(4, HexPoint::new(0, 0).hex_dist_to(HexPoint::new(-1, 4)));
+    }
+
+    #[test]
+    fn bounding_box_of_no_points() {
+        assert_eq!(None, BoundingBox::of_points(std::iter::empty()));
+    }
+
+    #[test]
+    fn bounding_box_size() {
+        let points = [Point::new(-1, 2), Point::new(3, 0), Point::new(0, 4)];
+        let bounds = BoundingBox::of_points(points.iter().cloned()).unwrap();
+
+        assert_eq!(Point::new(-1, 0), bounds.min());
+        assert_eq!(Point::new(3, 4), bounds.max());
+        assert_eq!(5, bounds.width());
+        assert_eq!(5, bounds.height());
+        assert_eq!(25, bounds.area());
+        assert_eq!(bounds.area(), bounds.coords().count());
+
+        let padded = bounds.expand(2);
+        assert_eq!(9 * 9, padded.area());
+        assert_eq!(Some(bounds), padded.intersection(&bounds));
+        assert_eq!(padded, padded.union(&bounds));
+    }
+
+    #[test]
+    fn bounding_box_union_and_intersection() {
+        let a = BoundingBox::new(Point::new(0, 0), Point::new(2, 2));
+        let b = BoundingBox::new(Point::new(4, 1), Point::new(1, 3));
+
+        assert_eq!(BoundingBox::new(Point::new(0, 0), Point::new(4, 3)), a.union(&b));
+        assert_eq!(Some(BoundingBox::new(Point::new(1, 1), Point::new(2, 2))), a.intersection(&b));
+
+        let far = BoundingBox::of_point(Point::new(10, 10));
+        assert_eq!(None, a.intersection(&far));
+
+        let mut extended = a;
+        extended.extend(Point::new(10, 10));
+        assert_eq!(a.union(&far), extended);
+    }
+
+    #[test]
+    fn parses_ragged_map() {
+        let grid = Grid::parse("#.#\n.\n##", |_, c| c);
+
+        assert_eq!(3, grid.width());
+        assert_eq!(3, grid.height());
+        assert_eq!(Some(&'#'), grid.get(Point::new(2, 0)));
+        assert_eq!(' ', grid[Point::new(1, 1)]);
+        assert_eq!(None, grid.get(Point::new(3, 0)));
+        assert_eq!(None, grid.get(Point::new(0, -1)));
+    }
+
+    #[test]
+    fn iterates_rows_and_columns() {
+        let grid = Grid::from_fn(3, 2, |p| p.y * 3 + p.x);
+
+        let rows: Vec<_> = grid.rows().map(|row| row.to_vec()).collect();
+        assert_eq!(vec![vec![0, 1, 2], vec![3, 4, 5]], rows);
+
+        let columns: Vec<Vec<_>> = grid.columns().map(|col| col.cloned().collect()).collect();
+        assert_eq!(vec![vec![0, 3], vec![1, 4], vec![2, 5]], columns);
+    }
+
+    #[test]
+    fn neighbors_stay_in_bounds() {
+        let grid = Grid::from_fn(3, 3, |p| p);
+
+        let corner: Vec<_> = grid.neighbors(Point::new(0, 0)).map(|(p, _)| p).collect();
+        assert_eq!(vec![Point::new(1, 0), Point::new(0, 1)], corner);
+
+        let center: Vec<_> = grid.neighbors(Point::new(1, 1)).map(|(_, cell)| *cell).collect();
+        assert_eq!(vec![Point::new(1, 0), Point::new(0, 1), Point::new(2, 1), Point::new(1, 2)], center);
+    }
+
+    #[test]
+    fn euclidean_measure_is_exact_far_apart() {
+        // the squared distance is past what a usize can hold
+        let a = Point::new(-3_000_000_000, 4_000_000_000);
+        let b = Point::new(3_000_000_000, -4_000_000_000);
+        assert_eq!(100_000_000_000_000_000_000, Metric::Euclidean.measure(a, b));
+        assert_eq!(36_000_000_000_000_000_000, Metric::Euclidean.axis_measure(6_000_000_000));
+
+        let c = Point::new(3_000_000_000, -4_000_000_001);
+        assert!(Metric::Euclidean.measure(a, b) < Metric::Euclidean.measure(a, c));
+    }
+}
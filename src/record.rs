@@ -0,0 +1,149 @@
+#![allow(unused)]
+
+use {
+    crate::args,
+    std::{
+        env,
+        fs,
+        io,
+        path::{Path, PathBuf},
+    },
+};
+
+/// Captures text frames of a tick-based simulation into a directory, one numbered file per
+/// recorded tick (`00000.txt`, `00001.txt`, ...), so they can be stepped through or stitched
+/// into an animation afterwards.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    dir: PathBuf,
+    every: usize,
+    first_tick: usize,
+    last_tick: Option<usize>,
+    frames: usize,
+}
+
+impl Recorder {
+    /// Records every tick into `dir`, which is created if it doesn't exist yet
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+
+        Ok(Self {
+            dir,
+            every: 1,
+            first_tick: 0,
+            last_tick: None,
+            frames: 0,
+        })
+    }
+
+    /// Only records ticks which are a multiple of `n` after the first tick of the range
+    pub fn every(mut self, n: usize) -> Self {
+        self.every = usize::max(1, n);
+        self
+    }
+
+    /// Only records ticks from `first` up to and including `last`, if given
+    pub fn ticks(mut self, first: usize, last: Option<usize>) -> Self {
+        self.first_tick = first;
+        self.last_tick = last;
+        self
+    }
+
+    /// Builds a recorder from the `--record <dir>` argument, along with the optional
+    /// `--record-every <n>` and `--record-ticks <first>..[last]` arguments
+    pub fn from_args() -> Option<Self> {
+        let dir = args::value("--record")?;
+
+        let every = args::value("--record-every")
+            .map(|n| n.parse().expect("--record-every must be a number"))
+            .unwrap_or(1);
+
+        let (first, last) = args::value("--record-ticks")
+            .map(|range| parse_tick_range(&range).expect("--record-ticks must look like 10..20"))
+            .unwrap_or((0, None));
+
+        let recorder = Recorder::new(dir).expect("failed to create recording directory");
+        Some(recorder.every(every).ticks(first, last))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The number of frames written so far
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// Whether `tick` falls in the recorded range and isn't skipped
+    pub fn wants(&self, tick: usize) -> bool {
+        tick >= self.first_tick
+            && self.last_tick.map_or(true, |last| tick <= last)
+            && (tick - self.first_tick) % self.every == 0
+    }
+
+    /// Whether no later tick will be recorded, so the simulation doesn't need to keep
+    /// rendering frames
+    pub fn is_done(&self, tick: usize) -> bool {
+        self.last_tick.map_or(false, |last| tick > last)
+    }
+
+    /// Writes the frame for `tick` if it's wanted. The frame is only drawn when it will be
+    /// written, since drawing is usually much slower than the tick itself.
+    pub fn record(&mut self, tick: usize, frame: impl FnOnce() -> String) -> io::Result<()> {
+        if !self.wants(tick) {
+            return Ok(());
+        }
+
+        let path = self.dir.join(format!("{:05}.txt", self.frames));
+        fs::write(path, format!("tick {}\n{}", tick, frame()))?;
+        self.frames += 1;
+
+        Ok(())
+    }
+}
+
+fn parse_tick_range(s: &str) -> Option<(usize, Option<usize>)> {
+    let dots = s.find("..")?;
+    let (first, last) = (&s[..dots], &s[dots + 2..]);
+
+    let first = if first.is_empty() { 0 } else { first.parse().ok()? };
+    let last = if last.is_empty() { None } else { Some(last.parse().ok()?) };
+
+    Some((first, last))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn skips_and_ranges() {
+        let dir = env::temp_dir().join(format!("aoc-record-test-{}", std::process::id()));
+        let mut recorder = Recorder::new(&dir).unwrap().every(3).ticks(2, Some(10));
+
+        for tick in 0..20 {
+            recorder.record(tick, || format!("frame {}", tick)).unwrap();
+        }
+
+        assert_eq!(3, recorder.frames());
+        assert!(recorder.is_done(11));
+        assert!(!recorder.is_done(10));
+
+        let last = fs::read_to_string(dir.join("00002.txt")).unwrap();
+        assert_eq!("tick 8\nframe 8", last);
+        assert!(!dir.join("00003.txt").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tick_ranges() {
+        assert_eq!(Some((5, Some(10))), parse_tick_range("5..10"));
+        assert_eq!(Some((5, None)), parse_tick_range("5.."));
+        assert_eq!(Some((0, Some(3))), parse_tick_range("..3"));
+        assert_eq!(None, parse_tick_range("5"));
+        assert_eq!(None, parse_tick_range("a..b"));
+    }
+}
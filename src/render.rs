@@ -0,0 +1,209 @@
+#![allow(unused)]
+
+use {
+    crate::point::{BoundingBox, Point},
+    std::fmt,
+};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Color {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    Grey,
+}
+
+impl Color {
+    fn ansi_code(self) -> u8 {
+        match self {
+            Color::Red => 31,
+            Color::Green => 32,
+            Color::Yellow => 33,
+            Color::Blue => 34,
+            Color::Magenta => 35,
+            Color::Cyan => 36,
+            Color::White => 37,
+            Color::Grey => 90,
+        }
+    }
+}
+
+/// A character to draw for one cell, optionally in colour
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Glyph {
+    pub ch: char,
+    pub color: Option<Color>,
+}
+
+impl Glyph {
+    pub fn new(ch: char) -> Self {
+        Glyph { ch, color: None }
+    }
+
+    pub fn colored(ch: char, color: Color) -> Self {
+        Glyph { ch, color: Some(color) }
+    }
+}
+
+impl From<char> for Glyph {
+    fn from(ch: char) -> Self {
+        Glyph::new(ch)
+    }
+}
+
+/// Draws a grid-like state as text, one row per line, asking a callback for the glyph of each
+/// cell. Rows can be followed by annotations in a panel to the right of the map.
+#[derive(Debug, Clone)]
+pub struct Renderer {
+    bounds: BoundingBox,
+    visible: Option<BoundingBox>,
+    colors: bool,
+}
+
+impl Renderer {
+    pub fn new(bounds: BoundingBox) -> Self {
+        Self {
+            bounds,
+            visible: Some(bounds),
+            colors: false,
+        }
+    }
+
+    /// Crops the output to a `width` by `height` window centred on `center`. The window is
+    /// moved back inside the bounds where it can be, so cropping near an edge doesn't waste
+    /// space on nothing.
+    pub fn viewport(mut self, center: Point, width: usize, height: usize) -> Self {
+        let fit = |center: isize, size: usize, min: isize, max: isize| {
+            let size = isize::min(size as isize, max - min + 1);
+            let start = isize::max(min, isize::min(center - size / 2, max - size + 1));
+            (start, start + size - 1)
+        };
+
+        let (min, max) = (self.bounds.min(), self.bounds.max());
+        let (min_x, max_x) = fit(center.x, width, min.x, max.x);
+        let (min_y, max_y) = fit(center.y, height, min.y, max.y);
+
+        self.visible = if width > 0 && height > 0 {
+            Some(BoundingBox::new(Point::new(min_x, min_y), Point::new(max_x, max_y)))
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Whether to draw glyphs in colour using ANSI escape codes
+    pub fn colors(mut self, enabled: bool) -> Self {
+        self.colors = enabled;
+        self
+    }
+
+    /// The area which will be drawn, or `None` if the viewport is empty
+    pub fn visible_area(&self) -> Option<BoundingBox> {
+        self.visible
+    }
+
+    pub fn render(&self, f: &mut impl fmt::Write, glyph_at: impl Fn(Point) -> Glyph) -> fmt::Result {
+        self.render_annotated(f, glyph_at, |_| None)
+    }
+
+    /// Draws the state with an annotation after each row which has one
+    pub fn render_annotated(&self,
+                            f: &mut impl fmt::Write,
+                            glyph_at: impl Fn(Point) -> Glyph,
+                            annotation: impl Fn(isize) -> Option<String>) -> fmt::Result {
+        let visible = match self.visible {
+            Some(visible) => visible,
+            None => return Ok(()),
+        };
+
+        for y in visible.min().y..=visible.max().y {
+            let mut current_color = None;
+
+            for x in visible.min().x..=visible.max().x {
+                let glyph = glyph_at(Point::new(x, y));
+
+                if self.colors && glyph.color != current_color {
+                    match glyph.color {
+                        Some(color) => write!(f, "\x1b[{}m", color.ansi_code())?,
+                        None => write!(f, "\x1b[0m")?,
+                    }
+                    current_color = glyph.color;
+                }
+
+                write!(f, "{}", glyph.ch)?;
+            }
+
+            if current_color.is_some() {
+                write!(f, "\x1b[0m")?;
+            }
+
+            if let Some(annotation) = annotation(y) {
+                write!(f, "   {}", annotation)?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn render_to_string(&self, glyph_at: impl Fn(Point) -> Glyph) -> String {
+        let mut out = String::new();
+        self.render(&mut out, glyph_at).expect("writing to a string can't fail");
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn checkerboard(p: Point) -> Glyph {
+        if (p.x + p.y) % 2 == 0 {
+            Glyph::colored('#', Color::Red)
+        } else {
+            Glyph::new('.')
+        }
+    }
+
+    #[test]
+    fn renders_rows_with_annotations() {
+        let renderer = Renderer::new(BoundingBox::new(Point::new(0, 0), Point::new(2, 1)));
+
+        let mut out = String::new();
+        renderer.render_annotated(&mut out, checkerboard, |y| if y == 1 {
+            Some("note".to_string())
+        } else {
+            None
+        }).unwrap();
+
+        assert_eq!("#.#\n.#.   note\n", out);
+    }
+
+    #[test]
+    fn viewport_stays_inside_bounds() {
+        let bounds = BoundingBox::new(Point::new(0, 0), Point::new(9, 9));
+
+        let centered = Renderer::new(bounds).viewport(Point::new(5, 5), 3, 3);
+        assert_eq!(Some(BoundingBox::new(Point::new(4, 4), Point::new(6, 6))), centered.visible_area());
+
+        let corner = Renderer::new(bounds).viewport(Point::new(0, 9), 4, 4);
+        assert_eq!(Some(BoundingBox::new(Point::new(0, 6), Point::new(3, 9))), corner.visible_area());
+
+        let oversized = Renderer::new(bounds).viewport(Point::new(0, 0), 20, 20);
+        assert_eq!(Some(bounds), oversized.visible_area());
+    }
+
+    #[test]
+    fn colors_runs_of_glyphs() {
+        let renderer = Renderer::new(BoundingBox::new(Point::new(0, 0), Point::new(1, 0)))
+            .colors(true);
+
+        let out = renderer.render_to_string(checkerboard);
+        assert_eq!("\x1b[31m#\x1b[0m.\n", out);
+    }
+}
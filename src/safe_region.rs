@@ -0,0 +1,174 @@
+#![allow(unused)]
+
+use {
+    crate::point::{BoundingBox, Metric, Point},
+};
+
+/// The cells whose distances to every one of a set of points add up to less than a limit.
+///
+/// The total distance is a convex function of the cell, so the region is convex and each row
+/// of it is a single span. Each row is found by searching for the lowest total along the row
+/// and then for where the total reaches the limit on either side, without visiting the cells
+/// in between.
+pub struct SafeRegion {
+    /// `(y, min_x, max_x)` for each row with any cells in the region, from top to bottom
+    pub spans: Vec<(isize, isize, isize)>,
+}
+
+impl SafeRegion {
+    /// Returns `None` if there are no points, since then every cell is safe
+    pub fn compute(points: &[Point], metric: Metric, limit: usize) -> Option<Self> {
+        let bounds = BoundingBox::of_points(points.iter().cloned())?;
+
+        // a cell more than limit / n past the points along either axis is more than that far
+        // from each of them, so their distances add up to at least the limit
+        let area = bounds.expand(limit / points.len());
+
+        let limit = limit as f64;
+        let total = |x: isize, y: isize| -> f64 {
+            points.iter().map(|point| metric.dist(*point, Point::new(x, y))).sum()
+        };
+
+        let mut spans = Vec::new();
+        for y in area.min().y..=area.max().y {
+            // the totals along the row fall and then rise, so the lowest is where they stop falling
+            let falling = |x: isize| total(x + 1, y) < total(x, y);
+            let lowest = first_where(area.min().x, area.max().x, |x| !falling(x));
+            if total(lowest, y) >= limit {
+                continue;
+            }
+
+            let min_x = first_where(area.min().x, lowest, |x| total(x, y) < limit);
+            let max_x = first_where(lowest, area.max().x, |x| total(x, y) >= limit) - 1;
+            spans.push((y, min_x, max_x));
+        }
+
+        Some(Self { spans })
+    }
+
+    /// The number of cells in the region
+    pub fn size(&self) -> usize {
+        self.spans.iter().map(|(_, min_x, max_x)| (max_x - min_x + 1) as usize).sum()
+    }
+
+    pub fn bounds(&self) -> Option<BoundingBox> {
+        let ends = self.spans.iter()
+            .flat_map(|&(y, min_x, max_x)| vec![Point::new(min_x, y), Point::new(max_x, y)]);
+
+        BoundingBox::of_points(ends)
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.spans.iter()
+            .any(|&(y, min_x, max_x)| y == point.y && (min_x..=max_x).contains(&point.x))
+    }
+}
+
+/// The first value in `lo..=hi` where `pred` holds, or `hi + 1` if it never does. `pred` must
+/// be false up to some value and true from then on.
+fn first_where(mut lo: isize, mut hi: isize, pred: impl Fn(isize) -> bool) -> isize {
+    hi += 1;
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    lo
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn example_points() -> Vec<Point> {
+        vec![
+            Point::new(1, 1),
+            Point::new(1, 6),
+            Point::new(8, 3),
+            Point::new(3, 4),
+            Point::new(5, 5),
+            Point::new(8, 9),
+        ]
+    }
+
+    fn safe_cells(points: &[Point], metric: Metric, limit: usize, area: BoundingBox) -> Vec<Point> {
+        area.coords()
+            .filter(|cell| {
+                let total: f64 = points.iter().map(|point| metric.dist(*point, *cell)).sum();
+                total < limit as f64
+            })
+            .collect()
+    }
+
+    #[test]
+    fn puzzle_example() {
+        let region = SafeRegion::compute(&example_points(), Metric::Manhattan, 32).unwrap();
+        assert_eq!(16, region.size());
+        assert_eq!(Some(BoundingBox::new(Point::new(2, 3), Point::new(6, 6))), region.bounds());
+    }
+
+    #[test]
+    fn matches_checking_every_cell() {
+        for &metric in &[Metric::Manhattan, Metric::Euclidean, Metric::Chebyshev] {
+            for &limit in &[1, 20, 32, 60, 200] {
+                let scatters = [
+                    example_points(),
+                    vec![Point::new(4, -2)],
+                    vec![Point::new(0, 0), Point::new(0, 0)],
+                ];
+
+                for points in &scatters {
+                    let region = SafeRegion::compute(points, metric, limit).unwrap();
+
+                    // far enough out that nothing past it can be safe
+                    let area = BoundingBox::of_points(points.iter().cloned())
+                        .unwrap()
+                        .expand(limit);
+                    let expected = safe_cells(points, metric, limit, area);
+
+                    assert_eq!(expected.len(), region.size(), "{} within {}", metric, limit);
+                    assert!(expected.iter().all(|cell| region.contains(*cell)),
+                            "{} within {}", metric, limit);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reaches_past_the_points() {
+        // one point alone leaves the bounding box of the points a single cell
+        let region = SafeRegion::compute(&[Point::new(0, 0)], Metric::Chebyshev, 3).unwrap();
+        assert_eq!(25, region.size());
+        assert_eq!(Some(BoundingBox::new(Point::new(-2, -2), Point::new(2, 2))), region.bounds());
+    }
+
+    #[test]
+    fn large_limit_reaches_far_past_the_points() {
+        let points = example_points();
+        let points_bounds = BoundingBox::of_points(points.iter().cloned()).unwrap();
+        let limit = 1200;
+
+        for &metric in &[Metric::Manhattan, Metric::Euclidean, Metric::Chebyshev] {
+            let region = SafeRegion::compute(&points, metric, limit).unwrap();
+            let bounds = region.bounds().unwrap();
+            assert!(bounds.width() > 10 * points_bounds.width(), "{}", metric);
+            assert!(bounds.height() > 10 * points_bounds.height(), "{}", metric);
+
+            // nothing further out than limit / n can be safe
+            let area = points_bounds.expand(limit / points.len() + 1);
+            let expected = safe_cells(&points, metric, limit, area);
+
+            assert_eq!(expected.len(), region.size(), "{}", metric);
+            let expected_bounds = BoundingBox::of_points(expected.iter().cloned());
+            assert_eq!(Some(bounds), expected_bounds, "{}", metric);
+        }
+    }
+
+    #[test]
+    fn no_points() {
+        assert!(SafeRegion::compute(&[], Metric::Manhattan, 10).is_none());
+    }
+}
@@ -0,0 +1,406 @@
+#![allow(unused)]
+
+use {
+    crate::{
+        args,
+        point::{BoundingBox, Direction, Point},
+        render::{Color, Glyph, Renderer},
+    },
+    std::{
+        fmt::{self, Write as _},
+        io::{self, BufRead, Write},
+    },
+};
+
+/// A tick-based simulation which can be driven by a `Stepper`
+pub trait Simulation: Clone {
+    fn bounds(&self) -> Option<BoundingBox>;
+
+    fn glyph_at(&self, pos: Point) -> Glyph;
+
+    /// Text to show to the right of row `y`, like the fighters' HP in day 15
+    fn annotation(&self, _y: isize) -> Option<String> {
+        None
+    }
+
+    /// Runs one tick, describing anything notable which happened during it
+    fn advance(&mut self) -> Option<String>;
+
+    fn is_finished(&self) -> bool;
+
+    /// Describes whatever is at `pos`, if there's anything
+    fn inspect(&self, pos: Point) -> Option<String>;
+}
+
+/// Whether `--step` was passed, asking for the interactive stepper instead of the normal run
+pub fn requested() -> bool {
+    args::flag("--step")
+}
+
+/// Ticks before "run to event" gives up, for simulations which never have another event
+const RUN_LIMIT: usize = 10_000;
+
+const HELP: &str = "\
+commands:
+  n [count]     step forward one tick, or `count` ticks (an empty line steps once)
+  e             run until something happens
+  g <tick>      go to a tick, replaying from the start if it's in the past
+  c <x> <y>     move the cursor to x,y
+  < > ^ v [n]   move the cursor one cell, or `n` cells
+  ?             show this help
+  q             quit";
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Command {
+    Step(usize),
+    RunToEvent,
+    GoTo(usize),
+    Cursor(Point),
+    Move(Direction, usize),
+    Help,
+    Quit,
+}
+
+impl Command {
+    fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+
+        let name = match words.next() {
+            Some(name) => name,
+            None => return Ok(Command::Step(1)),
+        };
+
+        let mut number = |what: &str| -> Result<Option<isize>, String> {
+            match words.next() {
+                Some(word) => word.parse()
+                    .map(Some)
+                    .map_err(|_| format!("{} must be a number, got `{}`", what, word)),
+                None => Ok(None),
+            }
+        };
+
+        let count = |n: Option<isize>| -> Result<usize, String> {
+            match n {
+                Some(n) if n < 0 => Err(format!("count must not be negative, got {}", n)),
+                Some(n) => Ok(n as usize),
+                None => Ok(1),
+            }
+        };
+
+        let direction = match name {
+            "<" => Some(Direction::West),
+            ">" => Some(Direction::East),
+            "^" => Some(Direction::North),
+            "v" => Some(Direction::South),
+            _ => None,
+        };
+
+        if let Some(direction) = direction {
+            return Ok(Command::Move(direction, count(number("count")?)?));
+        }
+
+        match name {
+            "n" => Ok(Command::Step(count(number("count")?)?)),
+            "e" => Ok(Command::RunToEvent),
+            "g" => match number("tick")? {
+                Some(tick) if tick >= 0 => Ok(Command::GoTo(tick as usize)),
+                _ => Err("usage: g <tick>".to_string()),
+            },
+            "c" => match (number("x")?, number("y")?) {
+                (Some(x), Some(y)) => Ok(Command::Cursor(Point::new(x, y))),
+                _ => Err("usage: c <x> <y>".to_string()),
+            },
+            "?" => Ok(Command::Help),
+            "q" => Ok(Command::Quit),
+            _ => Err(format!("unknown command `{}`, try `?`", name)),
+        }
+    }
+}
+
+/// Steps through a simulation in the terminal, reading one command per line and redrawing
+/// the state around a cursor after each one
+pub struct Stepper<S> {
+    initial: S,
+    state: S,
+    tick: usize,
+    cursor: Point,
+    view_width: usize,
+    view_height: usize,
+    last_event: Option<(usize, String)>,
+    message: Option<String>,
+}
+
+impl<S: Simulation> Stepper<S> {
+    pub fn new(initial: S) -> Self {
+        let bounds = initial.bounds();
+        let cursor = bounds.map_or(Point::new(0, 0), |bounds| bounds.min());
+
+        Self {
+            state: initial.clone(),
+            initial,
+            tick: 0,
+            cursor,
+            view_width: 80,
+            view_height: 30,
+            last_event: None,
+            message: Some("type ? for help".to_string()),
+        }
+    }
+
+    /// The size of the window around the cursor, for states too big for the terminal
+    pub fn viewport(mut self, width: usize, height: usize) -> Self {
+        self.view_width = width;
+        self.view_height = height;
+        self
+    }
+
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    /// Runs commands from stdin until `q` or the end of input
+    pub fn run_terminal(&mut self) -> io::Result<()> {
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        self.run(stdin.lock(), &mut stdout.lock())
+    }
+
+    pub fn run(&mut self, input: impl BufRead, output: &mut impl Write) -> io::Result<()> {
+        let mut lines = input.lines();
+
+        loop {
+            self.draw(output)?;
+            write!(output, "> ")?;
+            output.flush()?;
+
+            let line = match lines.next() {
+                Some(line) => line?,
+                None => break,
+            };
+
+            match Command::parse(&line) {
+                Ok(Command::Quit) => break,
+                Ok(command) => self.apply(command),
+                Err(message) => self.message = Some(message),
+            }
+        }
+
+        writeln!(output)
+    }
+
+    fn apply(&mut self, command: Command) {
+        match command {
+            Command::Step(count) => {
+                for _ in 0..count {
+                    if !self.advance() {
+                        break;
+                    }
+                }
+            }
+
+            Command::RunToEvent => {
+                let start = self.tick;
+                loop {
+                    if self.state.is_finished() {
+                        self.message = Some("simulation finished".to_string());
+                        break;
+                    }
+                    if self.tick - start >= RUN_LIMIT {
+                        self.message = Some(format!("no event in {} ticks", RUN_LIMIT));
+                        break;
+                    }
+
+                    let before = self.last_event.as_ref().map(|(tick, _)| *tick);
+                    self.advance();
+                    if self.last_event.as_ref().map(|(tick, _)| *tick) != before {
+                        break;
+                    }
+                }
+            }
+
+            Command::GoTo(tick) => {
+                if tick < self.tick {
+                    self.state = self.initial.clone();
+                    self.tick = 0;
+                    self.last_event = None;
+                }
+
+                while self.tick < tick && self.advance() {}
+            }
+
+            Command::Cursor(pos) => self.cursor = pos,
+
+            Command::Move(direction, count) => {
+                self.cursor += Point::from(direction) * count as isize;
+            }
+
+            Command::Help => self.message = Some(HELP.to_string()),
+
+            Command::Quit => {}
+        }
+    }
+
+    /// Runs one tick, unless the simulation has already finished
+    fn advance(&mut self) -> bool {
+        if self.state.is_finished() {
+            self.message = Some("simulation finished".to_string());
+            return false;
+        }
+
+        let event = self.state.advance();
+        self.tick += 1;
+
+        if let Some(event) = event {
+            self.last_event = Some((self.tick, event));
+        }
+
+        true
+    }
+
+    fn draw(&mut self, output: &mut impl Write) -> io::Result<()> {
+        let mut screen = String::new();
+        self.write_screen(&mut screen).expect("writing to a string can't fail");
+        output.write_all(screen.as_bytes())
+    }
+
+    fn write_screen(&mut self, screen: &mut String) -> fmt::Result {
+        // clear the terminal and move to the top left
+        write!(screen, "\x1b[2J\x1b[H")?;
+
+        let status = if self.state.is_finished() { " (finished)" } else { "" };
+        writeln!(screen, "tick {}{}   cursor {}", self.tick, status, self.cursor)?;
+
+        if let Some(bounds) = self.state.bounds() {
+            let renderer = Renderer::new(bounds)
+                .viewport(self.cursor, self.view_width, self.view_height)
+                .colors(true);
+
+            let cursor = self.cursor;
+            let state = &self.state;
+            renderer.render_annotated(
+                screen,
+                |pos| {
+                    let glyph = state.glyph_at(pos);
+                    if pos == cursor {
+                        Glyph::colored(if glyph.ch == ' ' { '_' } else { glyph.ch }, Color::Magenta)
+                    } else {
+                        glyph
+                    }
+                },
+                |y| state.annotation(y),
+            )?;
+        }
+
+        match self.state.inspect(self.cursor) {
+            Some(description) => writeln!(screen, "at cursor: {}", description)?,
+            None => writeln!(screen, "at cursor: nothing")?,
+        }
+
+        if let Some((tick, event)) = &self.last_event {
+            writeln!(screen, "last event (tick {}): {}", tick, event)?;
+        }
+
+        if let Some(message) = self.message.take() {
+            writeln!(screen, "{}", message)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A marker walking east along a row of 10 cells, with an event every third tick
+    #[derive(Clone)]
+    struct Walker {
+        x: isize,
+    }
+
+    impl Simulation for Walker {
+        fn bounds(&self) -> Option<BoundingBox> {
+            Some(BoundingBox::new(Point::new(0, 0), Point::new(9, 0)))
+        }
+
+        fn glyph_at(&self, pos: Point) -> Glyph {
+            Glyph::new(if pos.x == self.x { 'W' } else { '.' })
+        }
+
+        fn advance(&mut self) -> Option<String> {
+            self.x += 1;
+            if self.x % 3 == 0 {
+                Some(format!("reached {}", self.x))
+            } else {
+                None
+            }
+        }
+
+        fn is_finished(&self) -> bool {
+            self.x == 9
+        }
+
+        fn inspect(&self, pos: Point) -> Option<String> {
+            if pos.x == self.x {
+                Some("walker".to_string())
+            } else {
+                None
+            }
+        }
+    }
+
+    fn run(commands: &str) -> (Stepper<Walker>, String) {
+        let mut stepper = Stepper::new(Walker { x: 0 });
+        let mut output = Vec::new();
+        stepper.run(commands.as_bytes(), &mut output).unwrap();
+
+        (stepper, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(Ok(Command::Step(1)), Command::parse(""));
+        assert_eq!(Ok(Command::Step(5)), Command::parse("n 5"));
+        assert_eq!(Ok(Command::GoTo(12)), Command::parse("g 12"));
+        assert_eq!(Ok(Command::Cursor(Point::new(-1, 4))), Command::parse(" c -1 4 "));
+        assert_eq!(Ok(Command::Move(Direction::North, 3)), Command::parse("^ 3"));
+        assert!(Command::parse("g").is_err());
+        assert!(Command::parse("n -2").is_err());
+        assert!(Command::parse("x").is_err());
+    }
+
+    #[test]
+    fn steps_and_runs_to_events() {
+        let (stepper, _) = run("\nn 2\ne\n");
+        assert_eq!(6, stepper.tick());
+        assert_eq!(Some((6, "reached 6".to_string())), stepper.last_event);
+
+        // stops at the end instead of stepping past it
+        let (stepper, output) = run("n 20\ne\n");
+        assert_eq!(9, stepper.tick());
+        assert!(output.contains("tick 9 (finished)"));
+        assert!(output.contains("simulation finished"));
+    }
+
+    #[test]
+    fn goes_back_in_time() {
+        let (stepper, _) = run("n 7\ng 2\n");
+        assert_eq!(2, stepper.tick());
+        assert_eq!(2, stepper.state().x);
+        assert_eq!(None, stepper.last_event);
+    }
+
+    #[test]
+    fn inspects_under_cursor() {
+        let (stepper, output) = run("n 4\nc 4 0\nq\nn\n");
+        assert_eq!(4, stepper.tick());
+        assert!(output.contains("at cursor: walker"));
+
+        let (stepper, _) = run("> 3\nv\n");
+        assert_eq!(Point::new(3, 1), stepper.cursor);
+    }
+}
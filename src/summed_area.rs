@@ -0,0 +1,75 @@
+#![allow(unused)]
+
+use {
+    crate::point::{BoundingBox, Grid, Point},
+};
+
+/// Answers the sum of any rectangle of a grid of numbers in constant time.
+///
+/// Each entry holds the sum of every cell above and to the left of it, with an extra row and
+/// column of zeroes along the top and left so that rectangles touching the edge of the grid
+/// need no special cases.
+pub struct SummedAreaTable {
+    sums: Grid<isize>,
+}
+
+impl SummedAreaTable {
+    pub fn new(grid: &Grid<isize>) -> Self {
+        let mut sums = Grid::new(grid.width() + 1, grid.height() + 1, 0);
+
+        for (point, value) in grid.iter() {
+            let below_right = point + Point::new(1, 1);
+            sums[below_right] = value
+                + sums[below_right.north()]
+                + sums[below_right.west()]
+                - sums[point];
+        }
+
+        Self { sums }
+    }
+
+    pub fn width(&self) -> usize {
+        self.sums.width() - 1
+    }
+
+    pub fn height(&self) -> usize {
+        self.sums.height() - 1
+    }
+
+    /// The sum of the cells inside `area`, which must be inside the grid
+    pub fn sum(&self, area: BoundingBox) -> isize {
+        let min = area.min();
+        let max = area.max() + Point::new(1, 1);
+
+        self.sums[max]
+            - self.sums[Point::new(min.x, max.y)]
+            - self.sums[Point::new(max.x, min.y)]
+            + self.sums[min]
+    }
+
+    /// The sum of the `size` by `size` square with its top left corner at `top_left`
+    pub fn square_sum(&self, top_left: Point, size: usize) -> isize {
+        let bottom_right = top_left + Point::new(size as isize - 1, size as isize - 1);
+        self.sum(BoundingBox::new(top_left, bottom_right))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sums_match_brute_force() {
+        let grid = Grid::from_fn(7, 5, |p| (p.x * 3 - p.y * 5 + p.x * p.y) % 7);
+        let table = SummedAreaTable::new(&grid);
+
+        for a in grid.points() {
+            for b in grid.points() {
+                let area = BoundingBox::new(a, b);
+                let expected: isize = area.coords().map(|p| grid[p]).sum();
+
+                assert_eq!(expected, table.sum(area), "sum of {:?}", area);
+            }
+        }
+    }
+}
@@ -0,0 +1,608 @@
+#![allow(unused)]
+
+use {
+    crate::{
+        point::{BoundingBox, Metric, Point},
+        kdtree::KdTree,
+    },
+    std::cmp::Ordering,
+};
+
+/// How the regions which extend forever are told apart from the finite ones
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Extent {
+    /// Only cells inside the seeds' bounding box are considered, and any region which reaches
+    /// the edge of the box is assumed to be infinite
+    BoundingBox,
+
+    /// Regions are classified exactly for the chosen metric, and every cell of each finite
+    /// region is found, even when a region reaches outside the seeds' bounding box
+    Exact,
+}
+
+/// What happens to cells which are equally close to more than one seed
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TiePolicy {
+    /// Tied cells belong to no region, as in the puzzle
+    Unclaimed,
+    /// Tied cells belong to whichever of the tied seeds comes first
+    FirstSeed,
+    /// Tied cells belong to the regions of all the tied seeds, so regions can overlap
+    Shared,
+}
+
+impl TiePolicy {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "unclaimed" => Some(TiePolicy::Unclaimed),
+            "first" => Some(TiePolicy::FirstSeed),
+            "shared" => Some(TiePolicy::Shared),
+            _ => None,
+        }
+    }
+
+    /// The seeds which own a cell, given the seeds closest to it
+    fn owners(self, mut closest: Vec<usize>) -> Vec<usize> {
+        match self {
+            TiePolicy::Unclaimed if closest.len() > 1 => Vec::new(),
+            TiePolicy::FirstSeed => {
+                closest.truncate(1);
+                closest
+            }
+            TiePolicy::Unclaimed | TiePolicy::Shared => closest,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Region {
+    pub seed: usize,
+    pub cells: Vec<Point>,
+    pub finite: bool,
+}
+
+impl Region {
+    pub fn size(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+/// The cells closest to each of a set of seed points, with cells which are equally close to
+/// more than one seed handled by a `TiePolicy`
+#[derive(Debug, Clone)]
+pub struct Regions {
+    /// One region for each seed, in the same order as the seeds
+    pub regions: Vec<Region>,
+
+    /// Cells equally close to two or more seeds
+    pub ties: Vec<Point>,
+
+    /// The area which was searched. All cells of finite regions are inside it, but infinite
+    /// regions and the ties between them carry on past its edge
+    pub area: BoundingBox,
+}
+
+impl Regions {
+    /// Returns `None` if there are no seeds
+    pub fn compute(seeds: &[Point],
+                   metric: Metric,
+                   extent: Extent,
+                   tie_policy: TiePolicy) -> Option<Self> {
+        let bounds = BoundingBox::of_points(seeds.iter().cloned())?;
+        let index = KdTree::new(seeds);
+
+        let (area, infinite) = match (extent, metric) {
+            (Extent::BoundingBox, _) | (Extent::Exact, Metric::Manhattan) => {
+                // for manhattan distance, moving straight out from the box adds the same distance
+                // to every seed, so a region reaching the edge of the box never ends. finite
+                // regions are also always inside the box, since a shortest path from a seed to
+                // any of its cells only passes through cells of the same region.
+                (bounds, edge_owners(&index, bounds, metric, tie_policy))
+            }
+
+            (Extent::Exact, Metric::Chebyshev) => {
+                // past this distance from the box, moving away diagonally (or straight out, if
+                // still level with the box) adds the same distance to every seed
+                let padding = usize::max(bounds.width(), bounds.height());
+                let area = bounds.expand(padding);
+                (area, edge_owners(&index, area, metric, tie_policy))
+            }
+
+            (Extent::Exact, Metric::Euclidean) => {
+                let infinite: Vec<_> = (0..seeds.len())
+                    .map(|seed| on_convex_hull(seeds, seed))
+                    .collect();
+
+                // a region which gets its ties is a little bigger, taking in the cells along
+                // its edges as well
+                let closed = tie_policy != TiePolicy::Unclaimed;
+
+                let area = (0..seeds.len())
+                    .filter(|seed| !infinite[*seed])
+                    .filter_map(|seed| euclidean_region_bounds(seeds, seed, closed))
+                    .fold(bounds, |area, region_bounds| area.union(&region_bounds));
+
+                (area, infinite)
+            }
+        };
+
+        let mut regions: Vec<_> = infinite.iter()
+            .enumerate()
+            .map(|(seed, infinite)| Region { seed, cells: Vec::new(), finite: !infinite })
+            .collect();
+        let mut ties = Vec::new();
+
+        for cell in area.coords() {
+            let closest = closest_seeds(&index, cell, metric);
+            if closest.len() > 1 {
+                ties.push(cell);
+            }
+
+            for seed in tie_policy.owners(closest) {
+                regions[seed].cells.push(cell);
+            }
+        }
+
+        Some(Self { regions, ties, area })
+    }
+
+    pub fn finite(&self) -> impl Iterator<Item=&Region> {
+        self.regions.iter().filter(|region| region.finite)
+    }
+
+    pub fn largest_finite(&self) -> Option<&Region> {
+        self.finite().max_by_key(|region| region.size())
+    }
+}
+
+/// Every seed which is as close to `cell` as any other, in order
+fn closest_seeds(index: &KdTree, cell: Point, metric: Metric) -> Vec<usize> {
+    // there is rarely more than one, so only ask for more when they're all tied
+    let mut n = 2;
+    loop {
+        let closest = index.nearest_n(cell, n, metric);
+        let tied = match closest.first() {
+            Some(first) => {
+                closest.iter().take_while(|other| other.measure == first.measure).count()
+            }
+            None => return Vec::new(),
+        };
+
+        if tied < n {
+            return closest[..tied].iter().map(|nearest| nearest.index).collect();
+        }
+        n *= 2;
+    }
+}
+
+/// Which seeds own any of the cells along the edge of `area`
+fn edge_owners(index: &KdTree,
+               area: BoundingBox,
+               metric: Metric,
+               tie_policy: TiePolicy) -> Vec<bool> {
+    let mut owners = vec![false; index.len()];
+
+    let (min, max) = (area.min(), area.max());
+    let horizontal_edges = (min.x..=max.x)
+        .flat_map(|x| vec![Point::new(x, min.y), Point::new(x, max.y)]);
+    let vertical_edges = (min.y..=max.y)
+        .flat_map(|y| vec![Point::new(min.x, y), Point::new(max.x, y)]);
+
+    for cell in horizontal_edges.chain(vertical_edges) {
+        for seed in tie_policy.owners(closest_seeds(index, cell, metric)) {
+            owners[seed] = true;
+        }
+    }
+
+    owners
+}
+
+/// Whether a seed lies on the boundary of the convex hull of all the seeds, which is exactly
+/// when its euclidean region is unbounded: there is then some direction in which no other seed
+/// lies further out than it.
+fn on_convex_hull(seeds: &[Point], seed: usize) -> bool {
+    let origin = seeds[seed];
+    let mut directions: Vec<(i128, i128)> = seeds.iter()
+        .filter(|other| **other != origin)
+        .map(|other| ((other.x - origin.x) as i128, (other.y - origin.y) as i128))
+        .collect();
+
+    if directions.len() < 2 {
+        return true;
+    }
+
+    let cross = |a: (i128, i128), b: (i128, i128)| a.0 * b.1 - a.1 * b.0;
+    let dot = |a: (i128, i128), b: (i128, i128)| a.0 * b.0 + a.1 * b.1;
+
+    // order the directions by angle without rounding them: the half starting at the positive
+    // x axis first, then by which side of each other they're on
+    let second_half = |d: (i128, i128)| d.1 < 0 || (d.1 == 0 && d.0 < 0);
+    directions.sort_by(|a, b| {
+        second_half(*a).cmp(&second_half(*b)).then_with(|| cross(*b, *a).cmp(&0))
+    });
+
+    // the seed is on the hull if all the others fit in a half-plane through it, meaning there
+    // is a gap of at least half a turn between the directions to them
+    let half_turn_apart = |from, to| {
+        cross(from, to) < 0 || (cross(from, to) == 0 && dot(from, to) < 0)
+    };
+
+    let first = directions[0];
+    let last = directions[directions.len() - 1];
+    let all_one_way = directions.iter().all(|d| cross(first, *d) == 0 && dot(first, *d) > 0);
+
+    all_one_way
+        || half_turn_apart(last, first)
+        || directions.windows(2).any(|pair| half_turn_apart(pair[0], pair[1]))
+}
+
+/// A bound of the number line, as the fraction `num / den` with `den` always positive
+#[derive(Debug, Copy, Clone)]
+struct Fraction {
+    num: i128,
+    den: i128,
+}
+
+impl Fraction {
+    fn new(num: i128, den: i128) -> Self {
+        if den < 0 {
+            Fraction { num: -num, den: -den }
+        } else {
+            Fraction { num, den }
+        }
+    }
+
+    fn cmp(self, other: Self) -> Ordering {
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+
+    fn floor(self) -> isize {
+        self.num.div_euclid(self.den) as isize
+    }
+
+    fn ceil(self) -> isize {
+        -(Fraction { num: -self.num, den: self.den }.floor())
+    }
+}
+
+/// The range of `x` values on row `y` which are closer to `seeds[seed]` than to any other seed,
+/// or `None` if there are none. The region of a seed is the intersection of the half-planes on
+/// its side of the bisector with each other seed, so each of its rows is a single range. Bounds
+/// are `None` when the range is unbounded on that side.
+///
+/// The range is open, or closed to also take in the points which are only as close as some
+/// other seed.
+fn euclidean_row_span(seeds: &[Point],
+                      seed: usize,
+                      y: isize,
+                      closed: bool) -> Option<(Option<Fraction>, Option<Fraction>)> {
+    let origin = seeds[seed];
+    let mut lo: Option<Fraction> = None;
+    let mut hi: Option<Fraction> = None;
+
+    let others = seeds.iter().enumerate()
+        .filter(|(i, _)| *i != seed)
+        .map(|(_, other)| other);
+
+    for other in others {
+        // |p - origin|^2 < |p - other|^2  <=>  a * x < c
+        let (x0, y0) = (origin.x as i128, origin.y as i128);
+        let (x1, y1) = (other.x as i128, other.y as i128);
+        let a = 2 * (x1 - x0);
+        let c = x1 * x1 + y1 * y1 - x0 * x0 - y0 * y0 - 2 * y as i128 * (y1 - y0);
+
+        match a.cmp(&0) {
+            Ordering::Greater => {
+                let bound = Fraction::new(c, a);
+                if hi.map(|hi| bound.cmp(hi) == Ordering::Less).unwrap_or(true) {
+                    hi = Some(bound);
+                }
+            }
+            Ordering::Less => {
+                let bound = Fraction::new(c, a);
+                if lo.map(|lo| bound.cmp(lo) == Ordering::Greater).unwrap_or(true) {
+                    lo = Some(bound);
+                }
+            }
+            Ordering::Equal => if c < 0 || (c == 0 && !closed) {
+                return None;
+            }
+        }
+    }
+
+    match (lo, hi) {
+        (Some(lo), Some(hi)) if lo.cmp(hi) == Ordering::Greater => None,
+        (Some(lo), Some(hi)) if lo.cmp(hi) == Ordering::Equal && !closed => None,
+        span => Some(span),
+    }
+}
+
+/// The bounding box of the cells in a finite euclidean region, walking outwards row by row
+/// from the seed until the region runs out. `closed` includes the cells along its edges.
+fn euclidean_region_bounds(seeds: &[Point], seed: usize, closed: bool) -> Option<BoundingBox> {
+    let origin = seeds[seed];
+    let mut bounds: Option<BoundingBox> = None;
+
+    for step in [-1, 1].iter().cloned() {
+        let mut y = if step < 0 { origin.y } else { origin.y + 1 };
+
+        while let Some(span) = euclidean_row_span(seeds, seed, y, closed) {
+            let (lo, hi) = match span {
+                (Some(lo), Some(hi)) => (lo, hi),
+                _ => panic!("region of seed {} is unbounded on row {}", origin, y),
+            };
+
+            // cells in an open range are strictly between the bounds
+            let (min_x, max_x) = if closed {
+                (lo.ceil(), hi.floor())
+            } else {
+                (lo.floor() + 1, hi.ceil() - 1)
+            };
+            if min_x <= max_x {
+                let row = BoundingBox::new(Point::new(min_x, y), Point::new(max_x, y));
+                bounds = Some(bounds.map(|bounds| bounds.union(&row)).unwrap_or(row));
+            }
+
+            y += step;
+        }
+    }
+
+    bounds
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn example_seeds() -> Vec<Point> {
+        vec![
+            Point::new(1, 1),
+            Point::new(1, 6),
+            Point::new(8, 3),
+            Point::new(3, 4),
+            Point::new(5, 5),
+            Point::new(8, 9),
+        ]
+    }
+
+    /// The regions with ties going to no one, as in the puzzle
+    fn regions(seeds: &[Point], metric: Metric, extent: Extent) -> Regions {
+        Regions::compute(seeds, metric, extent, TiePolicy::Unclaimed).unwrap()
+    }
+
+    #[test]
+    fn finds_largest_finite_region_in_example() {
+        let regions = regions(&example_seeds(), Metric::Manhattan, Extent::BoundingBox);
+
+        let finite: Vec<_> = regions.finite().map(|region| region.seed).collect();
+        assert_eq!(vec![3, 4], finite);
+
+        let largest = regions.largest_finite().unwrap();
+        assert_eq!(4, largest.seed);
+        assert_eq!(17, largest.size());
+    }
+
+    #[test]
+    fn exact_manhattan_matches_bounding_box() {
+        let seeds = example_seeds();
+        let approx = regions(&seeds, Metric::Manhattan, Extent::BoundingBox);
+        let exact = regions(&seeds, Metric::Manhattan, Extent::Exact);
+
+        for (a, b) in approx.regions.iter().zip(exact.regions.iter()) {
+            assert_eq!(a.finite, b.finite);
+            assert_eq!(a.cells, b.cells);
+        }
+        assert_eq!(approx.ties, exact.ties);
+    }
+
+    #[test]
+    fn exact_chebyshev_region_reaches_past_bounding_box() {
+        // the middle seed reaches the top edge of the box, but is boxed in further up by the
+        // seeds either side of it
+        let seeds = vec![
+            Point::new(0, 0),
+            Point::new(4, 0),
+            Point::new(2, 1),
+            Point::new(2, 4),
+        ];
+
+        let approx = regions(&seeds, Metric::Chebyshev, Extent::BoundingBox);
+        let exact = regions(&seeds, Metric::Chebyshev, Extent::Exact);
+
+        assert!(!approx.regions[2].finite);
+        assert!(exact.regions[2].finite);
+        assert!(!exact.regions[3].finite);
+
+        let expected_cells = vec![
+            Point::new(2, 0),
+            Point::new(2, 1),
+            Point::new(1, 2),
+            Point::new(2, 2),
+            Point::new(3, 2),
+        ];
+        assert_eq!(expected_cells, exact.regions[2].cells);
+        assert!(exact.ties.contains(&Point::new(2, -1)));
+    }
+
+    #[test]
+    fn exact_euclidean_region_reaches_past_bounding_box() {
+        // the seed just above the bottom edge of the hull is finite, but its region stretches
+        // a long way below the other seeds
+        let seeds = vec![
+            Point::new(0, 0),
+            Point::new(20, 0),
+            Point::new(10, 20),
+            Point::new(10, 1),
+        ];
+
+        let approx = regions(&seeds, Metric::Euclidean, Extent::BoundingBox);
+        let exact = regions(&seeds, Metric::Euclidean, Extent::Exact);
+
+        assert!(!approx.regions[3].finite);
+        assert!(exact.regions[3].finite);
+        assert!(exact.regions[..3].iter().all(|region| !region.finite));
+
+        let region = &exact.regions[3];
+        assert!(region.cells.contains(&Point::new(10, -40)));
+        assert!(region.cells.iter().all(|cell| {
+            let dist = Metric::Euclidean.measure(*cell, seeds[3]);
+            seeds[..3].iter().all(|other| Metric::Euclidean.measure(*cell, *other) > dist)
+        }));
+
+        // nothing is missed below the area that was searched
+        let below = Point::new(10, exact.area.min().y - 1);
+        let measure = |seed: Point| Metric::Euclidean.measure(below, seed);
+        assert!(measure(seeds[0]) <= measure(seeds[3]));
+    }
+
+    #[test]
+    fn exact_euclidean_regions_far_from_the_origin() {
+        // squaring these coordinates overflows an isize, so the regions are only the same as
+        // near the origin if the arithmetic is exact
+        let seeds = vec![
+            Point::new(0, 0),
+            Point::new(20, 0),
+            Point::new(10, 20),
+            Point::new(10, 1),
+        ];
+        let offset = Point::new(3_000_000_000, -3_000_000_000);
+        let far_seeds: Vec<_> = seeds.iter().map(|seed| *seed + offset).collect();
+
+        let near = regions(&seeds, Metric::Euclidean, Extent::Exact);
+        let far = regions(&far_seeds, Metric::Euclidean, Extent::Exact);
+
+        for (near, far) in near.regions.iter().zip(&far.regions) {
+            assert_eq!(near.finite, far.finite);
+            let shifted: Vec<_> = near.cells.iter().map(|cell| *cell + offset).collect();
+            assert_eq!(shifted, far.cells);
+        }
+    }
+
+    #[test]
+    fn tie_policies() {
+        let seeds = example_seeds();
+        let index = KdTree::new(&seeds);
+
+        for &metric in &[Metric::Manhattan, Metric::Euclidean, Metric::Chebyshev] {
+            let with_policy = |tie_policy| {
+                Regions::compute(&seeds, metric, Extent::BoundingBox, tie_policy).unwrap()
+            };
+
+            let unclaimed = with_policy(TiePolicy::Unclaimed);
+            let first = with_policy(TiePolicy::FirstSeed);
+            let shared = with_policy(TiePolicy::Shared);
+
+            assert_eq!(unclaimed.ties, first.ties);
+            assert_eq!(unclaimed.ties, shared.ties);
+
+            for tie in &unclaimed.ties {
+                let closest = closest_seeds(&index, *tie, metric);
+                assert!(closest.len() > 1);
+
+                let owners = |regions: &Regions| -> Vec<usize> {
+                    regions.regions.iter()
+                        .filter(|region| region.cells.contains(tie))
+                        .map(|region| region.seed)
+                        .collect()
+                };
+
+                assert!(owners(&unclaimed).is_empty());
+                assert_eq!(vec![closest[0]], owners(&first));
+                assert_eq!(closest, owners(&shared));
+            }
+        }
+    }
+
+    #[test]
+    fn exact_regions_with_ties_match_a_wide_search() {
+        let seeds = vec![
+            Point::new(0, 0),
+            Point::new(6, 0),
+            Point::new(3, 2),
+            Point::new(3, 6),
+            Point::new(0, 6),
+            Point::new(6, 6),
+            Point::new(3, 3),
+        ];
+        let index = KdTree::new(&seeds);
+
+        for &metric in &[Metric::Manhattan, Metric::Euclidean, Metric::Chebyshev] {
+            for &tie_policy in &[TiePolicy::Unclaimed, TiePolicy::FirstSeed, TiePolicy::Shared] {
+                let exact = Regions::compute(&seeds, metric, Extent::Exact, tie_policy).unwrap();
+                let wide = exact.area.expand(30);
+
+                for region in exact.finite() {
+                    let expected: Vec<_> = wide.coords()
+                        .filter(|cell| {
+                            let owners = tie_policy.owners(closest_seeds(&index, *cell, metric));
+                            owners.contains(&region.seed)
+                        })
+                        .collect();
+
+                    assert_eq!(expected, region.cells,
+                               "{} {:?} seed {}", metric, tie_policy, region.seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hull_check_is_exact_far_from_the_origin() {
+        // the third seed is just inside the hull, so close to the edge between the first two
+        // that the directions to them are within a billionth of a half turn
+        let seeds = vec![
+            Point::new(0, 0),
+            Point::new(20_000_000_000, 0),
+            Point::new(10_000_000_000, 1),
+            Point::new(10_000_000_000, 10),
+        ];
+
+        let on_hull: Vec<_> = (0..seeds.len()).map(|seed| on_convex_hull(&seeds, seed)).collect();
+        assert_eq!(vec![true, true, false, true], on_hull);
+
+        // a seed in the middle of an edge of the hull is on it
+        let seeds = vec![Point::new(0, 0), Point::new(4, 2), Point::new(8, 4), Point::new(4, 9)];
+        assert!(on_convex_hull(&seeds, 1));
+    }
+
+    #[test]
+    fn exact_chebyshev_matches_a_wide_search() {
+        // fixed pseudo-random scatters, which include some duplicate seeds
+        let mut state = 12345u64;
+        let mut next = move |range: u64| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((state >> 33) % range) as isize
+        };
+
+        for _ in 0..20 {
+            let count = 2 + next(8) as usize;
+            let seeds: Vec<_> = (0..count).map(|_| Point::new(next(12), next(8))).collect();
+            let index = KdTree::new(&seeds);
+
+            let exact = regions(&seeds, Metric::Chebyshev, Extent::Exact);
+
+            let bounds = BoundingBox::of_points(seeds.iter().cloned()).unwrap();
+            let wide = bounds.expand(4 * usize::max(bounds.width(), bounds.height()));
+            let reaches_edge = edge_owners(&index, wide, Metric::Chebyshev, TiePolicy::Unclaimed);
+
+            for region in &exact.regions {
+                // a seed with a duplicate never has any cells of its own
+                if seeds.iter().filter(|other| **other == seeds[region.seed]).count() > 1 {
+                    continue;
+                }
+
+                let context = format!("seed {} of {:?}", region.seed, seeds);
+                assert_eq!(!reaches_edge[region.seed], region.finite, "{}", context);
+
+                if region.finite {
+                    let expected: Vec<_> = wide.coords()
+                        .filter(|cell| {
+                            closest_seeds(&index, *cell, Metric::Chebyshev) == vec![region.seed]
+                        })
+                        .collect();
+                    assert_eq!(expected, region.cells, "{}", context);
+                }
+            }
+        }
+    }
+}